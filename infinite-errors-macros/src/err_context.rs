@@ -0,0 +1,628 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::{
+    parse::Parser, parse_quote, punctuated::Punctuated, spanned::Spanned, visit_mut::VisitMut,
+    Attribute, Block, Expr, ExprPath, FnArg, GenericArgument, Ident, ImplItem, Item, Meta, Pat,
+    PathArguments, ReturnType, Signature, Token, TraitItem, Type, TypeParamBound,
+};
+
+const UNSUPPORTED_ITEM: &str =
+    "`err_context` can only be applied to functions, `impl` blocks and traits";
+
+/// What a function returns, as far as `err_context` is concerned.
+enum Output {
+    /// A `Result`, either directly or from an `async fn`.
+    Result,
+    /// An `impl Future<Output = Result<..>>`.
+    ImplFuture,
+    /// A `Pin<Box<dyn Future<Output = Result<..>>>>`, such as the methods
+    /// expanded by `async_trait`.
+    BoxedFuture,
+}
+
+/// How the error kind of the context is built. Either way, it only happens
+/// when the function returns an error.
+enum Kind {
+    /// `#[err_context(ErrorKind::Variant)]`: an expression.
+    Expr(Expr),
+    /// `#[err_context(with = function)]`: a function returning the kind.
+    With(Expr),
+}
+
+/// Context added to the errors of a single function.
+struct Options {
+    /// Kind of the new frame, or `None` for `function`.
+    kind: Option<Kind>,
+    /// Arguments whose debug strings are recorded with the function frame,
+    /// from `args(a, b)`.
+    args: Option<Vec<Ident>>,
+    /// Arguments used by the kind or `args` which are cloned before the body
+    /// runs, because it moves them, from `clone(a, b)`.
+    clones: Vec<Ident>,
+}
+
+impl Options {
+    /// Whether a function frame with the name and location of the function is
+    /// always recorded. Otherwise, it is only recorded if the crate being
+    /// compiled sets the `INFINITE_ERRORS_FUNCTION_CONTEXT` environment
+    /// variable.
+    fn records_function(&self) -> bool {
+        self.kind.is_none() || self.args.is_some()
+    }
+}
+
+/// The environment variable a crate sets for its own compilation, such as
+/// with `cargo:rustc-env` in its build script, to record a function frame for
+/// every function with `err_context`. It is read by `option_env!` in the
+/// expansion, rather than by this macro, so that the compiler tracks it, and it
+/// does not leak into other crates like a feature would.
+const FUNCTION_CONTEXT: &str = "INFINITE_ERRORS_FUNCTION_CONTEXT";
+
+/// Context of a single function: either the context to add or `skip`.
+enum Context {
+    Wrap(Box<Options>),
+    Skip,
+}
+
+impl Context {
+    fn parse(attributes: TokenStream) -> syn::Result<Self> {
+        if attributes.is_empty() {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "missing error kind, use `#[err_context(ErrorKind::Variant)]` or `#[err_context(function)]`",
+            ));
+        }
+
+        let arguments = Punctuated::<Expr, Token![,]>::parse_terminated.parse2(attributes)?;
+        let mut arguments = arguments.into_iter();
+        let kind = match arguments.next() {
+            Some(Expr::Path(path)) if path.path.is_ident("skip") => {
+                if let Some(argument) = arguments.next() {
+                    return Err(syn::Error::new(
+                        argument.span(),
+                        "`skip` does not take other arguments",
+                    ));
+                }
+                return Ok(Self::Skip);
+            }
+            Some(Expr::Path(path)) if path.path.is_ident("function") => None,
+            Some(Expr::Assign(assign)) if is_ident(&assign.left, "with") => {
+                Some(Kind::With(*assign.right))
+            }
+            Some(kind) => Some(Kind::Expr(kind)),
+            None => {
+                return Err(syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    "missing error kind",
+                ))
+            }
+        };
+
+        let mut args = None;
+        let mut clones = None;
+        for argument in arguments {
+            match argument {
+                Expr::Call(call) if is_ident(&call.func, "args") && args.is_none() => {
+                    args = Some(argument_names(call.args)?);
+                }
+                Expr::Call(call) if is_ident(&call.func, "clone") && clones.is_none() => {
+                    clones = Some(argument_names(call.args)?);
+                }
+                argument => {
+                    return Err(syn::Error::new(
+                        argument.span(),
+                        "unexpected argument, expected `args(...)` or `clone(...)`",
+                    ))
+                }
+            }
+        }
+        let clones = clones.unwrap_or_default();
+
+        Ok(Self::Wrap(Box::new(Options { kind, args, clones })))
+    }
+}
+
+pub(crate) fn err_context_impl(
+    attributes: TokenStream,
+    item: TokenStream,
+) -> syn::Result<TokenStream> {
+    let context = Context::parse(attributes)?;
+    let Context::Wrap(options) = context else {
+        return Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "`skip` can only be used on methods of an `impl` block or trait with `err_context`",
+        ));
+    };
+    let item =
+        syn::parse2::<Item>(item).map_err(|err| syn::Error::new(err.span(), UNSUPPORTED_ITEM))?;
+
+    match item {
+        Item::Fn(mut item_fn) => {
+            wrap_body(&item_fn.sig, &mut item_fn.block, &options, None, true)?;
+            Ok(item_fn.into_token_stream())
+        }
+        Item::Impl(mut item_impl) => {
+            let scope = match &*item_impl.self_ty {
+                Type::Path(path) => path.path.segments.last().map(|x| x.ident.clone()),
+                _ => None,
+            };
+            for item in &mut item_impl.items {
+                if let ImplItem::Fn(method) = item {
+                    wrap_method(
+                        &mut method.attrs,
+                        &method.sig,
+                        &mut method.block,
+                        &options,
+                        scope.as_ref(),
+                    )?;
+                }
+            }
+            Ok(item_impl.into_token_stream())
+        }
+        Item::Trait(mut item_trait) => {
+            for item in &mut item_trait.items {
+                if let TraitItem::Fn(method) = item {
+                    if let Some(block) = &mut method.default {
+                        wrap_method(
+                            &mut method.attrs,
+                            &method.sig,
+                            block,
+                            &options,
+                            Some(&item_trait.ident),
+                        )?;
+                    }
+                }
+            }
+            Ok(item_trait.into_token_stream())
+        }
+        item => Err(syn::Error::new(item.span(), UNSUPPORTED_ITEM)),
+    }
+}
+
+/// Wrap the body of a method of an `impl` block or trait with the context of
+/// the whole block, unless the method has its own `err_context` attribute.
+///
+/// Methods which do not return a `Result` are left untouched, unless they have
+/// their own attribute.
+///
+/// Function frames are named after `scope`, the type or trait of the block.
+fn wrap_method(
+    attrs: &mut Vec<Attribute>,
+    sig: &Signature,
+    block: &mut Block,
+    options: &Options,
+    scope: Option<&Ident>,
+) -> syn::Result<()> {
+    let Some(index) = attrs.iter().position(is_err_context) else {
+        return wrap_body(sig, block, options, scope, false);
+    };
+
+    let attr = attrs.remove(index);
+    let span = attr.span();
+    let tokens = match attr.meta {
+        Meta::List(list) => list.tokens,
+        _ => TokenStream::new(),
+    };
+    match Context::parse(tokens).map_err(|err| syn::Error::new(span, err))? {
+        Context::Wrap(options) => wrap_body(sig, block, &options, scope, true),
+        Context::Skip => Ok(()),
+    }
+}
+
+/// Parse the argument names of `args(...)` or `clone(...)`.
+fn argument_names(names: Punctuated<Expr, Token![,]>) -> syn::Result<Vec<Ident>> {
+    names
+        .into_iter()
+        .map(|name| match name {
+            Expr::Path(path) if path.path.get_ident().is_some() => {
+                Ok(path.path.get_ident().unwrap().clone())
+            }
+            name => Err(syn::Error::new(name.span(), "expected an argument name")),
+        })
+        .collect()
+}
+
+fn is_ident(expr: &Expr, ident: &str) -> bool {
+    matches!(expr, Expr::Path(path) if path.path.is_ident(ident))
+}
+
+fn is_err_context(attr: &Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "err_context")
+}
+
+/// Wrap the body of a function so that errors it returns get the given
+/// context.
+///
+/// If `strict` is set, functions which cannot be wrapped are an error.
+/// Otherwise they are left untouched.
+fn wrap_body(
+    sig: &Signature,
+    block: &mut Block,
+    options: &Options,
+    scope: Option<&Ident>,
+    strict: bool,
+) -> syn::Result<()> {
+    let output = match check_signature(sig) {
+        Ok(output) => output,
+        Err(err) if strict => return Err(err),
+        Err(_) => return Ok(()),
+    };
+    let mut visitor = CaptureArguments::new(sig, &options.clones)?;
+    let kind = options.kind.as_ref().map(|kind| visitor.kind(kind));
+    let args: Vec<_> = options
+        .args
+        .iter()
+        .flatten()
+        .map(|argument| visitor.argument(argument))
+        .collect();
+    let function = options.records_function().then(|| {
+        function_frame(
+            sig,
+            scope,
+            options.args.as_deref().unwrap_or_default(),
+            &args,
+        )
+    });
+    let (captures, borrows) = visitor.finish()?;
+    if borrows && !matches!(output, Output::Result) {
+        return Err(syn::Error::new(
+            sig.ident.span(),
+            "the future of the function cannot borrow the arguments used by the error kind \
+            or `args`, list them in `clone(...)`",
+        ));
+    }
+    let location = function.is_some().then(|| {
+        quote_spanned! {sig.ident.span()=>
+            let __err_context_location = ::core::panic::Location::caller();
+        }
+    });
+    let captures = location.into_iter().chain(captures);
+
+    // The body only borrows the arguments used on error, so that they can
+    // still be used once it returns
+    let capture = (!borrows).then(|| quote! {move});
+    let result = match output {
+        Output::Result if sig.asyncness.is_some() => quote! {
+            (async #capture
+                #block
+            ).await
+        },
+        Output::Result => quote! {
+            (#capture | |
+                #block
+            )()
+        },
+        Output::ImplFuture | Output::BoxedFuture => quote! {future.await},
+    };
+    let mut result = add_context(result, kind, function);
+    if !options.records_function() {
+        let name = function_name(sig, scope);
+        let location = quote_spanned! {sig.ident.span()=>
+            ::core::panic::Location::caller()
+        };
+        result = quote! {
+            ::infinite_errors::__private::function_context(
+                #result,
+                ::core::option_env!(#FUNCTION_CONTEXT),
+                ::core::concat!(::core::module_path!(), "::", #name),
+                #location,
+            )
+        };
+    }
+
+    let body = match output {
+        Output::Result => quote! {
+            #(#captures)*
+            #result
+        },
+        Output::ImplFuture => quote! {
+            #(#captures)*
+            let future = (#capture | | #block)();
+            async move {
+                #result
+            }
+        },
+        Output::BoxedFuture => quote! {
+            #(#captures)*
+            let future = (#capture | | #block)();
+            ::infinite_errors::__private::Box::pin(async move {
+                #result
+            })
+        },
+    };
+    *block = parse_quote! {{ #body }};
+
+    Ok(())
+}
+
+/// Add the context to the result of the body of a function: a new frame with
+/// the given kind, if one exists, and then a function frame on the outermost
+/// frame, if one is recorded.
+///
+/// The function frame is built before the kind, which may move the clones of
+/// the arguments it records.
+fn add_context(
+    result: TokenStream,
+    kind: Option<TokenStream>,
+    function: Option<TokenStream>,
+) -> TokenStream {
+    let Some(function) = function else {
+        return match kind {
+            Some(kind) => quote! {
+                ::infinite_errors::ErrorContext::err_context_with(#result, #kind)
+            },
+            None => result,
+        };
+    };
+
+    let err = match kind {
+        Some(kind) => quote! {
+            ::infinite_errors::ErrorContext::err_context_with(
+                ::core::result::Result::Err(x),
+                #kind,
+            )
+        },
+        None => quote! {
+            ::core::result::Result::Err(::core::convert::Into::into(x))
+        },
+    };
+    quote! {
+        match #result {
+            ::core::result::Result::Ok(x) => ::core::result::Result::Ok(x),
+            ::core::result::Result::Err(x) => {
+                let function = #function;
+                ::infinite_errors::FunctionContext::function_context(#err, move | | function)
+            }
+        }
+    }
+}
+
+/// Build the function frame of a function, named after the module, the
+/// `scope` and the function itself, with the debug strings of the given
+/// arguments. `values` are the expressions of the arguments, or of their
+/// clones.
+fn function_frame(
+    sig: &Signature,
+    scope: Option<&Ident>,
+    args: &[Ident],
+    values: &[TokenStream],
+) -> TokenStream {
+    let name = function_name(sig, scope);
+    let arg_names = args.iter().map(ToString::to_string);
+
+    quote! {
+        ::infinite_errors::FunctionFrame::new(
+            ::core::concat!(::core::module_path!(), "::", #name),
+            __err_context_location,
+            ::infinite_errors::__private::vec![
+                #((#arg_names, ::infinite_errors::__private::format!("{:?}", #values))),*
+            ],
+        )
+    }
+}
+
+/// Get the name of a function, after the `scope` it is in, if any, without the
+/// module.
+fn function_name(sig: &Signature, scope: Option<&Ident>) -> String {
+    match scope {
+        Some(scope) => format!("{}::{}", scope, sig.ident),
+        None => sig.ident.to_string(),
+    }
+}
+
+/// Find the arguments of a function used on error, by the kind or `args`,
+/// including `self`. Those listed in `clone(...)` are cloned before the body
+/// runs, since the body moves them, and the others are borrowed by the body.
+struct CaptureArguments<'a> {
+    arguments: Vec<Ident>,
+    clones: &'a [Ident],
+    captured: Vec<Ident>,
+    borrowed: bool,
+}
+
+impl<'a> CaptureArguments<'a> {
+    fn new(sig: &'a Signature, clones: &'a [Ident]) -> syn::Result<Self> {
+        let arguments: Vec<_> = sig
+            .inputs
+            .iter()
+            .filter_map(|input| match input {
+                FnArg::Typed(input) => match &*input.pat {
+                    Pat::Ident(pat) => Some(pat.ident.clone()),
+                    _ => None,
+                },
+                FnArg::Receiver(receiver) => Some(receiver.self_token.into()),
+            })
+            .collect();
+        if let Some(clone) = clones.iter().find(|x| !arguments.contains(x)) {
+            return Err(syn::Error::new(
+                clone.span(),
+                format!("`{clone}` is not an argument of the function"),
+            ));
+        }
+
+        Ok(Self {
+            arguments,
+            clones,
+            captured: Vec::new(),
+            borrowed: false,
+        })
+    }
+
+    /// Get the function building the kind, using the clones of the arguments
+    /// to clone.
+    fn kind(&mut self, kind: &Kind) -> TokenStream {
+        match kind {
+            Kind::Expr(expr) => {
+                let mut expr = expr.clone();
+                let borrowed = std::mem::take(&mut self.borrowed);
+                self.visit_expr_mut(&mut expr);
+                let capture = (!self.borrowed).then(|| quote! {move});
+                self.borrowed |= borrowed;
+                quote! {#capture | | #expr}
+            }
+            Kind::With(function) => {
+                let mut function = function.clone();
+                self.visit_expr_mut(&mut function);
+                function.into_token_stream()
+            }
+        }
+    }
+
+    /// Get the expression of an argument recorded by `args`, or of its clone.
+    fn argument(&mut self, argument: &Ident) -> TokenStream {
+        let mut expr: Expr = parse_quote! {#argument};
+        self.visit_expr_mut(&mut expr);
+        if !self.arguments.contains(argument) {
+            // a variable of the enclosing scope
+            self.borrowed = true;
+        }
+        expr.into_token_stream()
+    }
+
+    /// Get the statements cloning the arguments to clone, and whether the body
+    /// has to borrow some arguments.
+    fn finish(self) -> syn::Result<(Vec<TokenStream>, bool)> {
+        if let Some(clone) = self.clones.iter().find(|x| !self.captured.contains(x)) {
+            return Err(syn::Error::new(
+                clone.span(),
+                format!("`{clone}` is not used by the error kind or `args`"),
+            ));
+        }
+        let captures = self
+            .captured
+            .iter()
+            .map(|argument| {
+                let captured = captured_ident(argument);
+                quote! {let #captured = ::core::clone::Clone::clone(&#argument);}
+            })
+            .collect();
+
+        Ok((captures, self.borrowed))
+    }
+}
+
+impl VisitMut for CaptureArguments<'_> {
+    fn visit_expr_path_mut(&mut self, expr: &mut ExprPath) {
+        let Some(ident) = expr.path.get_ident() else {
+            return;
+        };
+        if expr.qself.is_some() || !self.arguments.contains(ident) {
+            return;
+        }
+        if !self.clones.contains(ident) {
+            self.borrowed = true;
+            return;
+        }
+
+        if !self.captured.contains(ident) {
+            self.captured.push(ident.clone());
+        }
+        expr.path = captured_ident(ident).into();
+    }
+}
+
+fn captured_ident(argument: &Ident) -> Ident {
+    format_ident!("__err_context_{}", argument)
+}
+
+/// Check that `err_context` can wrap the body of a function with the given
+/// signature.
+fn check_signature(sig: &Signature) -> syn::Result<Output> {
+    if let Some(constness) = sig.constness {
+        return Err(syn::Error::new(
+            constness.span(),
+            "`err_context` cannot be applied to `const fn`",
+        ));
+    }
+
+    let ty = match &sig.output {
+        ReturnType::Default => {
+            return Err(syn::Error::new(
+                sig.paren_token.span.close(),
+                "`err_context` requires the function to return a `Result`",
+            ))
+        }
+        ReturnType::Type(_, ty) => &**ty,
+    };
+
+    if is_result(ty) {
+        return Ok(Output::Result);
+    }
+    if sig.asyncness.is_none() {
+        if let Type::ImplTrait(impl_trait) = ty {
+            if future_output(&impl_trait.bounds).is_some_and(is_result) {
+                return Ok(Output::ImplFuture);
+            }
+        }
+        let boxed = type_argument(ty, "Pin").and_then(|ty| type_argument(ty, "Box"));
+        if let Some(Type::TraitObject(trait_object)) = boxed {
+            if future_output(&trait_object.bounds).is_some_and(is_result) {
+                return Ok(Output::BoxedFuture);
+            }
+        }
+    }
+
+    Err(syn::Error::new(
+        ty.span(),
+        "`err_context` requires the function to return a `Result`",
+    ))
+}
+
+/// Check whether a type looks like a `Result`: either `Result` itself or an
+/// alias whose name ends with `Result`, such as `io::Result` or `AppResult`.
+fn is_result(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident.to_string().ends_with("Result")),
+        Type::Group(group) => is_result(&group.elem),
+        Type::Paren(paren) => is_result(&paren.elem),
+        _ => false,
+    }
+}
+
+/// Get the `Output` of a `Future` bound, if there is one.
+fn future_output(bounds: &Punctuated<TypeParamBound, Token![+]>) -> Option<&Type> {
+    bounds.iter().find_map(|bound| {
+        let TypeParamBound::Trait(bound) = bound else {
+            return None;
+        };
+        let segment = bound.path.segments.last()?;
+        if segment.ident != "Future" {
+            return None;
+        }
+        let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+            return None;
+        };
+
+        arguments.args.iter().find_map(|argument| match argument {
+            GenericArgument::AssocType(assoc) if assoc.ident == "Output" => Some(&assoc.ty),
+            _ => None,
+        })
+    })
+}
+
+/// Get the single type argument of a type such as `Pin<T>` or `Box<T>` with
+/// the given name.
+fn type_argument<'a>(ty: &'a Type, name: &str) -> Option<&'a Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != name {
+        return None;
+    }
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+
+    arguments.args.iter().find_map(|argument| match argument {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    })
+}
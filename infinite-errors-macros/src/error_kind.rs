@@ -0,0 +1,340 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    parse::ParseStream, spanned::Spanned, Attribute, Data, DeriveInput, Fields, Ident, LitBool,
+    LitInt, LitStr, Member, Token, Variant,
+};
+
+/// How a variant is displayed.
+enum DisplayAttribute {
+    /// `#[error("format string", args...)]`
+    Format { format: LitStr, args: TokenStream },
+    /// `#[error(transparent)]`: display the single field of the variant.
+    Transparent,
+}
+
+/// Parsed `#[error(...)]` attribute.
+fn parse_display(variant: &Variant) -> syn::Result<DisplayAttribute> {
+    let mut attributes = variant
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("error"));
+    let Some(attr) = attributes.next() else {
+        return Err(syn::Error::new(
+            variant.ident.span(),
+            "missing `#[error(\"...\")]` attribute on variant",
+        ));
+    };
+    if let Some(duplicate) = attributes.next() {
+        return Err(syn::Error::new(
+            duplicate.span(),
+            "duplicate `#[error(...)]` attribute",
+        ));
+    }
+
+    attr.parse_args_with(|input: ParseStream| {
+        if input.peek(syn::Ident) {
+            let ident: Ident = input.parse()?;
+            if ident != "transparent" {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "expected a format string or `transparent`",
+                ));
+            }
+            if variant.fields.len() != 1 {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "`#[error(transparent)]` requires exactly one field",
+                ));
+            }
+
+            return Ok(DisplayAttribute::Transparent);
+        }
+
+        let format: LitStr = input.parse()?;
+        let format = LitStr::new(&rewrite_positional_fields(&format.value()), format.span());
+        let args = if input.is_empty() {
+            TokenStream::new()
+        } else {
+            let comma = input.parse::<Token![,]>()?;
+            let args: TokenStream = input.parse()?;
+            quote! {#comma #args}
+        };
+
+        Ok(DisplayAttribute::Format { format, args })
+    })
+}
+
+/// Rewrite references to tuple fields in a format string, such as `{0}` or
+/// `{1:?}`, to the names the fields are bound to, `{_0}` and `{_1:?}`.
+pub(crate) fn rewrite_positional_fields(format: &str) -> String {
+    let mut output = String::with_capacity(format.len());
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        output.push(c);
+        match c {
+            '{' if chars.peek() == Some(&'{') => output.push(chars.next().unwrap()),
+            '{' if chars.peek().is_some_and(char::is_ascii_digit) => output.push('_'),
+            _ => {}
+        }
+    }
+
+    output
+}
+
+/// Parsed `#[kind(...)]` attribute.
+#[derive(Default)]
+struct Metadata {
+    code: Option<LitStr>,
+    severity: Option<Ident>,
+    status: Option<LitInt>,
+    grpc: Option<Ident>,
+    public: Option<LitBool>,
+    problem_type: Option<LitStr>,
+    title: Option<LitStr>,
+    /// Bare `status`, to implement `ErrorStatus` with the defaults.
+    status_default: bool,
+}
+
+impl Metadata {
+    /// Whether this attribute asks for `ErrorStatus` to be implemented.
+    fn has_status(&self) -> bool {
+        self.status.is_some()
+            || self.grpc.is_some()
+            || self.public.is_some()
+            || self.problem_type.is_some()
+            || self.title.is_some()
+            || self.status_default
+    }
+}
+
+fn parse_metadata(attrs: &[Attribute]) -> syn::Result<Metadata> {
+    let mut metadata = Metadata::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("kind")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("code") {
+                metadata.code = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("severity") {
+                metadata.severity = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("status") {
+                if !meta.input.peek(Token![=]) {
+                    metadata.status_default = true;
+                    return Ok(());
+                }
+                let status: LitInt = meta.value()?.parse()?;
+                status.base10_parse::<u16>()?;
+                metadata.status = Some(status);
+            } else if meta.path.is_ident("grpc") {
+                metadata.grpc = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("problem_type") {
+                metadata.problem_type = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("title") {
+                metadata.title = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("public") {
+                metadata.public = Some(if meta.input.peek(Token![=]) {
+                    meta.value()?.parse()?
+                } else {
+                    LitBool::new(true, meta.path.span())
+                });
+            } else {
+                return Err(meta.error(
+                    "unknown argument, expected `code`, `severity`, `status`, `grpc`, `public`, \
+                     `problem_type` or `title`",
+                ));
+            }
+
+            Ok(())
+        })?;
+    }
+
+    Ok(metadata)
+}
+
+pub(crate) fn error_kind_impl(input: TokenStream) -> syn::Result<TokenStream> {
+    let input = syn::parse2::<DeriveInput>(input)?;
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "`ErrorKind` can only be derived for enums",
+        ));
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let default_metadata = parse_metadata(&input.attrs)?;
+    let mut has_status = default_metadata.has_status();
+
+    let mut display_arms = Vec::new();
+    let mut code_arms = Vec::new();
+    let mut severity_arms = Vec::new();
+    let mut status_arms = Vec::new();
+    let mut grpc_arms = Vec::new();
+    let mut public_arms = Vec::new();
+    let mut problem_type_arms = Vec::new();
+    let mut title_arms = Vec::new();
+    let mut from_impls = Vec::new();
+    for variant in &data.variants {
+        let variant_ident = &variant.ident;
+        let bindings: Vec<Ident> = variant
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| match &field.ident {
+                Some(ident) => ident.clone(),
+                None => format_ident!("_{}", i),
+            })
+            .collect();
+        let pattern = match &variant.fields {
+            Fields::Named(_) => quote! {Self::#variant_ident { #(#bindings),* }},
+            Fields::Unnamed(_) => quote! {Self::#variant_ident(#(#bindings),*)},
+            Fields::Unit => quote! {Self::#variant_ident},
+        };
+        let wildcard = match &variant.fields {
+            Fields::Named(_) => quote! {Self::#variant_ident { .. }},
+            Fields::Unnamed(_) => quote! {Self::#variant_ident(..)},
+            Fields::Unit => quote! {Self::#variant_ident},
+        };
+
+        let body = match parse_display(variant)? {
+            DisplayAttribute::Format { format, args } => quote! {
+                ::core::write!(__formatter, #format #args)
+            },
+            DisplayAttribute::Transparent => {
+                let field = &bindings[0];
+                quote! {::core::fmt::Display::fmt(#field, __formatter)}
+            }
+        };
+        display_arms.push(quote! {
+            #[allow(unused_variables)]
+            #pattern => #body,
+        });
+
+        let metadata = parse_metadata(&variant.attrs)?;
+        has_status |= metadata.has_status();
+        let code = match metadata.code.or_else(|| default_metadata.code.clone()) {
+            Some(code) => quote! {::core::option::Option::Some(#code)},
+            None => quote! {::core::option::Option::None},
+        };
+        code_arms.push(quote! {#wildcard => #code,});
+        let severity = metadata
+            .severity
+            .or_else(|| default_metadata.severity.clone())
+            .unwrap_or_else(|| format_ident!("Error"));
+        severity_arms.push(quote! {#wildcard => ::infinite_errors::Severity::#severity,});
+        let status = metadata
+            .status
+            .or_else(|| default_metadata.status.clone())
+            .map_or_else(|| quote! {500}, |status| quote! {#status});
+        status_arms.push(quote! {#wildcard => #status,});
+        let grpc = match metadata.grpc.or_else(|| default_metadata.grpc.clone()) {
+            Some(grpc) => quote! {::infinite_errors::GrpcCode::#grpc},
+            None => quote! {::infinite_errors::GrpcCode::from_http_status(#status)},
+        };
+        grpc_arms.push(quote! {#wildcard => #grpc,});
+        let public = metadata
+            .public
+            .or_else(|| default_metadata.public.clone())
+            .is_some_and(|public| public.value);
+        public_arms.push(quote! {#wildcard => #public,});
+        let problem_type = metadata
+            .problem_type
+            .or_else(|| default_metadata.problem_type.clone())
+            .map_or_else(|| quote! {"about:blank"}, |x| quote! {#x});
+        problem_type_arms.push(quote! {#wildcard => #problem_type,});
+        let title = match metadata.title.or_else(|| default_metadata.title.clone()) {
+            Some(title) => quote! {::core::option::Option::Some(#title)},
+            None => quote! {::core::option::Option::None},
+        };
+        title_arms.push(quote! {#wildcard => #title,});
+
+        for (i, field) in variant.fields.iter().enumerate() {
+            if !field.attrs.iter().any(|attr| attr.path().is_ident("from")) {
+                continue;
+            }
+            if variant.fields.len() != 1 {
+                return Err(syn::Error::new(
+                    field.span(),
+                    "`#[from]` requires the variant to have exactly one field",
+                ));
+            }
+
+            let ty = &field.ty;
+            let member = match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(i.into()),
+            };
+            from_impls.push(quote! {
+                impl #impl_generics ::core::convert::From<#ty> for #ident #ty_generics #where_clause {
+                    fn from(source: #ty) -> Self {
+                        Self::#variant_ident { #member: source }
+                    }
+                }
+            });
+        }
+    }
+
+    // Only implemented when asked for, so that kinds can implement it by hand
+    let status_impl = has_status.then(|| {
+        quote! {
+            impl #impl_generics ::infinite_errors::ErrorStatus for #ident #ty_generics #where_clause {
+                fn http_status(&self) -> u16 {
+                    match self {
+                        #(#status_arms)*
+                    }
+                }
+
+                fn grpc_code(&self) -> ::infinite_errors::GrpcCode {
+                    match self {
+                        #(#grpc_arms)*
+                    }
+                }
+
+                fn is_public(&self) -> bool {
+                    match self {
+                        #(#public_arms)*
+                    }
+                }
+
+                fn problem_type(&self) -> &'static str {
+                    match self {
+                        #(#problem_type_arms)*
+                    }
+                }
+
+                fn title(&self) -> ::core::option::Option<&'static str> {
+                    match self {
+                        #(#title_arms)*
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::core::fmt::Display for #ident #ty_generics #where_clause {
+            fn fmt(&self, __formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                match self {
+                    #(#display_arms)*
+                }
+            }
+        }
+
+        impl #impl_generics ::infinite_errors::KindMetadata for #ident #ty_generics #where_clause {
+            fn code(&self) -> ::core::option::Option<&'static str> {
+                match self {
+                    #(#code_arms)*
+                }
+            }
+
+            fn severity(&self) -> ::infinite_errors::Severity {
+                match self {
+                    #(#severity_arms)*
+                }
+            }
+        }
+
+        #status_impl
+
+        #(#from_impls)*
+    })
+}
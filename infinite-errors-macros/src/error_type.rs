@@ -0,0 +1,170 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    parse::Parser, punctuated::Punctuated, spanned::Spanned, Attribute, Fields, Ident, ItemStruct,
+    Path, Token, Type,
+};
+
+/// Arguments of the `error_type` attribute.
+#[derive(Default)]
+struct Arguments {
+    kind: Option<Type>,
+    prefix: Option<Ident>,
+    context: Option<Ident>,
+    source_context: Option<Ident>,
+    option_context: Option<Ident>,
+    future_context: Option<Ident>,
+    stream_context: Option<Ident>,
+    result: Option<Ident>,
+}
+
+impl Arguments {
+    fn parse(attributes: TokenStream) -> syn::Result<Self> {
+        let mut arguments = Self::default();
+        let parser = syn::meta::parser(|meta| {
+            if meta.path.is_ident("kind") {
+                arguments.kind = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("prefix") {
+                arguments.prefix = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("context") {
+                arguments.context = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("source_context") {
+                arguments.source_context = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("option_context") {
+                arguments.option_context = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("future_context") {
+                arguments.future_context = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("stream_context") {
+                arguments.stream_context = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("result") {
+                arguments.result = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error(
+                    "unknown argument, expected one of `kind`, `prefix`, `context`, `source_context`, \
+                     `option_context`, `future_context`, `stream_context` or `result`",
+                ));
+            }
+
+            Ok(())
+        });
+        parser.parse2(attributes)?;
+
+        Ok(arguments)
+    }
+}
+
+pub(crate) fn error_type_impl(
+    attributes: TokenStream,
+    item: TokenStream,
+) -> syn::Result<TokenStream> {
+    let arguments = Arguments::parse(attributes)?;
+    let item_struct = syn::parse2::<ItemStruct>(item)?;
+
+    if !matches!(item_struct.fields, Fields::Unit) {
+        return Err(syn::Error::new(
+            item_struct.fields.span(),
+            "the error type must be declared as a unit struct, its fields are generated",
+        ));
+    }
+    if !item_struct.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            item_struct.generics.span(),
+            "generic error types are not supported",
+        ));
+    }
+    let Some(kind) = arguments.kind else {
+        return Err(syn::Error::new(
+            item_struct.ident.span(),
+            "missing error kind, add `kind = YourErrorKind` to the attribute",
+        ));
+    };
+
+    let ItemStruct {
+        attrs, vis, ident, ..
+    } = item_struct;
+    let attrs = attrs
+        .into_iter()
+        .filter_map(|attr| supported_derives(attr).transpose())
+        .collect::<syn::Result<Vec<_>>>()?;
+    let prefix = arguments
+        .prefix
+        .map_or_else(String::new, |prefix| prefix.to_string());
+    let context = arguments
+        .context
+        .unwrap_or_else(|| format_ident!("{}ErrorContext", prefix));
+    let source_context = arguments
+        .source_context
+        .unwrap_or_else(|| format_ident!("{}SourceContext", prefix));
+    let option_context = arguments
+        .option_context
+        .unwrap_or_else(|| format_ident!("{}OptionContext", prefix));
+    let future_context = arguments
+        .future_context
+        .unwrap_or_else(|| format_ident!("{}FutureErrorContext", prefix));
+    let stream_context = arguments
+        .stream_context
+        .unwrap_or_else(|| format_ident!("{}TryStreamErrorContext", prefix));
+    let result = arguments.result.map(|result| quote! {result = #result;});
+
+    Ok(quote! {
+        ::infinite_errors::__declare_error_type! {
+            #(#attrs)*
+            #vis struct #ident;
+            kind = #kind;
+            context = #context;
+            source_context = #source_context;
+            option_context = #option_context;
+            future_context = #future_context;
+            stream_context = #stream_context;
+            #result
+        }
+    })
+}
+
+/// Traits which cannot be derived for an error type, because its generated
+/// fields, such as the foreign source and the attachments, don't implement
+/// them.
+const UNSUPPORTED_DERIVES: &[&str] = &[
+    "Copy",
+    "Default",
+    "PartialEq",
+    "Eq",
+    "PartialOrd",
+    "Ord",
+    "Hash",
+];
+
+/// Check the derives of the error type. `Debug` is always derived, so it is
+/// removed from the list, and the standard traits the generated fields don't
+/// implement are rejected. Other derives, such as `Clone`, and other
+/// attributes are kept as is.
+fn supported_derives(attr: Attribute) -> syn::Result<Option<Attribute>> {
+    if !attr.path().is_ident("derive") {
+        return Ok(Some(attr));
+    }
+
+    let derives = attr.parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)?;
+    let mut kept = Vec::new();
+    for derive in derives {
+        let name = derive
+            .segments
+            .last()
+            .map(|x| x.ident.to_string())
+            .unwrap_or_default();
+        if name == "Debug" {
+            continue;
+        }
+        if UNSUPPORTED_DERIVES.contains(&name.as_str()) {
+            return Err(syn::Error::new(
+                derive.span(),
+                format!(
+                    "`{name}` cannot be derived for an error type: its generated fields, \
+                     such as the foreign source, only implement `Debug` and `Clone`"
+                ),
+            ));
+        }
+        kept.push(derive);
+    }
+
+    Ok((!kept.is_empty()).then(|| syn::parse_quote! {#[derive(#(#kept),*)]}))
+}
@@ -1,18 +1,21 @@
 extern crate proc_macro;
 
+mod err_context;
+mod error_kind;
+mod error_type;
 #[cfg(test)]
 mod test;
 
 use proc_macro::TokenStream;
-use quote::{quote, ToTokens};
-use syn::{parse_quote, ItemFn, TypePath};
 
 /// Add a context when this function returns an error.
 ///
 /// This macro takes a single argument: the error kind which serves as
 /// context. This macro is compatible with both sync and async functions. The
 /// function must return a `Result<T, Error>` for some `T`, where `Error` is
-/// an error type generated by the `declare_error_type` macro.
+/// an error type generated by the `declare_error_type` macro. Aliases of
+/// `Result` are accepted as long as their name ends with `Result`. It cannot
+/// be applied to `const fn`.
 ///
 /// ```ignore
 /// #[err_context(ErrorKind::Parse)]
@@ -20,39 +23,194 @@ use syn::{parse_quote, ItemFn, TypePath};
 ///     // ...
 /// }
 /// ```
+///
+/// The error kind is only built when the function returns an error, so it
+/// can be costly. It can refer to the arguments of the function, `self`
+/// included, which the body then only borrows. If the body moves some of them, list them in
+/// `clone(a, b)`: they are cloned before the body runs, even if it succeeds.
+/// Functions returning a future must list all the arguments the kind uses.
+/// Arguments used inside macros in the kind, such as `format!`, are not
+/// detected. The kind can also come from a function, or closure, called on
+/// error:
+///
+/// ```ignore
+/// #[err_context(ErrorKind::Parse(path.clone()))]
+/// fn parse(path: &Path, text: String) -> Result<Config, Error> {
+///     // ...
+/// }
+///
+/// #[err_context(ErrorKind::Load(path), clone(path))]
+/// fn load(path: PathBuf) -> Result<String, Error> {
+///     read(path)
+/// }
+///
+/// #[err_context(with = load_error_kind)]
+/// fn load_config() -> Result<String, Error> {
+///     // ...
+/// }
+/// ```
+///
+/// With `#[err_context(function)]`, no new frame is added. Instead, the
+/// fully qualified name and the location of the function are recorded on the
+/// outermost frame of the error, see `ErrorType::functions`. They are not a
+/// frame of their own: they have no kind, so they do not show up in the
+/// display string or the fingerprint of the error, only in its report. An
+/// `args(a, b)` list, which can follow `function` or a kind, records the
+/// debug strings of the given arguments too. Like the kind, they are only
+/// formatted when the function returns an error, so the body borrows them,
+/// unless they are listed in `clone(a, b)`.
+///
+/// ```ignore
+/// #[err_context(function, args(id))]
+/// fn load_user(id: u64) -> Result<User, Error> {
+///     // ...
+/// }
+/// ```
+///
+/// A crate can make every function with `err_context` record its name and
+/// location by setting the `INFINITE_ERRORS_FUNCTION_CONTEXT` environment
+/// variable when it is compiled, from its build script. It is read with
+/// `option_env!`, so changing it rebuilds the crate. Other crates are not
+/// affected.
+///
+/// ```ignore
+/// // build.rs
+/// fn main() {
+///     println!("cargo:rustc-env=INFINITE_ERRORS_FUNCTION_CONTEXT=1");
+/// }
+/// ```
+///
+/// Functions returning `impl Future<Output = Result<..>>` or
+/// `Pin<Box<dyn Future<Output = Result<..>>>>` are supported too, so this
+/// macro can be put below `#[async_trait]`.
+///
+/// When applied to an `impl` block or a trait, every method (or default
+/// method) returning a `Result` gets the context. Other methods are left
+/// untouched. A method can override the context with its own attribute, or
+/// opt out with `#[err_context(skip)]`.
+///
+/// ```ignore
+/// #[async_trait]
+/// #[err_context(ErrorKind::Repository)]
+/// impl Repository for Postgres {
+///     async fn load(&self, id: u64) -> Result<User, Error> {
+///         // ...
+///     }
+///
+///     #[err_context(ErrorKind::Save)]
+///     async fn save(&self, user: &User) -> Result<(), Error> {
+///         // ...
+///     }
+///
+///     #[err_context(skip)]
+///     async fn ping(&self) -> Result<(), Error> {
+///         // ...
+///     }
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn err_context(attributes: TokenStream, item: TokenStream) -> TokenStream {
-    err_context_impl(attributes.into(), item.into()).into()
+    err_context::err_context_impl(attributes.into(), item.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
 
-fn err_context_impl(
-    attributes: proc_macro2::TokenStream,
-    item: proc_macro2::TokenStream,
-) -> proc_macro2::TokenStream {
-    let context_error_kind = unwrap_parse("attributes", syn::parse2::<TypePath>(attributes));
-    let mut item_fn = unwrap_parse("item", syn::parse2::<ItemFn>(item));
-    let (left, right) = if item_fn.sig.asyncness.is_some() {
-        (quote! {async move}, quote! {.await})
-    } else {
-        (quote! {move | |}, quote! {()})
-    };
-    let block = item_fn.block;
-    let body = parse_quote! {{
-        ::infinite_errors::ErrorContext::err_context(
-            (#left
-                #block
-            )#right,
-            #context_error_kind
-        )
-    }};
-    item_fn.block = Box::new(body);
-
-    item_fn.into_token_stream()
+/// Derive `Display`, `From`, `KindMetadata` and, if asked for, `ErrorStatus`
+/// for an error kind enum.
+///
+/// Every variant must have an `#[error(...)]` attribute:
+///
+/// - `#[error("format string", args...)]` displays the variant with the
+///   given format string. Fields can be referenced by name, such as
+///   `{path}`, or by position for tuple variants, such as `{0}` or `{1:?}`.
+/// - `#[error(transparent)]` displays the single field of the variant.
+///
+/// A field marked with `#[from]` generates a `From` implementation from the
+/// type of that field, so that `?` converts it into the error type generated
+/// for this kind. The variant must have exactly one field.
+///
+/// The `#[kind(code = "...", severity = ...)]` attribute sets the metadata
+/// returned by `KindMetadata`. It can be put on the enum, which sets the
+/// default for every variant, or on a variant. The severity is a variant of
+/// `Severity` and defaults to `Error`.
+///
+/// The same attribute sets the values returned by `ErrorStatus`: `status` is
+/// the HTTP status and defaults to 500, `grpc` is a variant of `GrpcCode` and
+/// defaults to the code matching the HTTP status, and `public` (or
+/// `public = false`) controls whether the message of the variant can be shown
+/// to clients. Variants are not public by default. `problem_type` and `title`
+/// set the RFC 7807 problem type URI and title, which default to
+/// `about:blank` and the reason phrase of the HTTP status. `ErrorStatus` is
+/// only derived if one of these is set, on the enum or on any variant, so
+/// that it can be implemented by hand otherwise. A bare `#[kind(status)]` on
+/// the enum derives it with the defaults.
+///
+/// ```ignore
+/// #[derive(Debug, ErrorKind)]
+/// #[kind(code = "E0000")]
+/// pub enum ErrorKind {
+///     #[error("failed to load {path}")]
+///     #[kind(code = "E0001", severity = Warning, status = 404, public)]
+///     Load { path: String },
+///     #[error("io error")]
+///     Io(#[from] std::io::Error),
+///     #[error("invalid value: {0:?}")]
+///     Invalid(u32),
+/// }
+/// ```
+#[proc_macro_derive(ErrorKind, attributes(error, from, kind))]
+pub fn error_kind(item: TokenStream) -> TokenStream {
+    error_kind::error_kind_impl(item.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
 
-fn unwrap_parse<T>(name: &str, res: syn::parse::Result<T>) -> T {
-    match res {
-        Ok(x) => x,
-        Err(err) => panic!("failed to parse {name}: {err}"),
-    }
+/// Generate a rich error type using a given error kind.
+///
+/// This is a more flexible version of `declare_error_type`. It is applied to
+/// a unit struct, which gives the name, the visibility and any extra
+/// attributes (such as documentation) of the error type. The fields of the
+/// struct are generated.
+///
+/// The error type always derives `Debug`, which can still be listed in a
+/// `#[derive]`. Other derives are passed through, so `#[derive(Clone)]`
+/// works as long as the kind implements `Clone`: clones share the foreign
+/// source, the backtrace and the attached values. Deriving `Copy`,
+/// `Default`, `PartialEq`, `Eq`, `PartialOrd`, `Ord` or `Hash` is rejected,
+/// as the generated fields don't implement them.
+///
+/// Arguments:
+///
+/// - `kind` (required): the error kind. Any type is accepted, including
+///   paths such as `crate::kinds::Kind`.
+/// - `prefix`: a prefix for the names of all the generated traits, such as
+///   `DbErrorContext` and `DbOptionContext` with `prefix = Db`. Names given
+///   with the arguments below are kept as is.
+/// - `context`: the name of the generated `ErrorContext` trait.
+/// - `source_context`: the name of the generated `SourceContext` trait.
+/// - `option_context`: the name of the generated `OptionContext` trait.
+/// - `future_context`, `stream_context`: the names of the
+///   `FutureErrorContext` and `TryStreamErrorContext` traits generated with
+///   the `future` feature of `infinite-errors`.
+/// - `result`: if present, the name of a generated `Result<T>` alias using
+///   this error type.
+///
+/// The traits have the same default names as with `declare_error_type`, so
+/// switching from one macro to the other doesn't change the imports. To
+/// declare more than one error type in the same module, give the others a
+/// `prefix`.
+///
+/// ```ignore
+/// #[error_type(kind = crate::kinds::Kind, result = AppResult)]
+/// #[derive(Clone)]
+/// pub(crate) struct AppError;
+///
+/// #[error_type(kind = crate::kinds::DbKind, prefix = Db)]
+/// pub(crate) struct DbError;
+/// ```
+#[proc_macro_attribute]
+pub fn error_type(attributes: TokenStream, item: TokenStream) -> TokenStream {
+    error_type::error_type_impl(attributes.into(), item.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
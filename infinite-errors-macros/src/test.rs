@@ -1,40 +1,39 @@
 use pretty_assertions::assert_eq;
 use quote::quote;
-use syn::{parse_quote, ItemFn};
 
 #[test]
 fn err_context_empty_fn() {
-    let output = err_context(quote! {fn f() {}});
+    let output = err_context(quote! {fn f() -> Result<()> {}});
 
-    assert_err_context_output(output, quote! {fn f()}, quote! {});
+    assert_err_context_output(output, quote! {fn f() -> Result<()>}, quote! {});
 }
 
 #[test]
 fn err_context_empty_async_fn() {
-    let output = err_context(quote! {async fn f() {}});
+    let output = err_context(quote! {async fn f() -> Result<()> {}});
 
-    assert_err_context_output(output, quote! {async fn f()}, quote! {});
+    assert_err_context_output(output, quote! {async fn f() -> Result<()>}, quote! {});
 }
 
 #[test]
 fn err_context_empty_generic_fn() {
-    let output = err_context(quote! {fn f<T>() {}});
+    let output = err_context(quote! {fn f<T>() -> Result<()> {}});
 
-    assert_err_context_output(output, quote! {fn f<T>()}, quote! {});
+    assert_err_context_output(output, quote! {fn f<T>() -> Result<()>}, quote! {});
 }
 
 #[test]
 fn err_context_empty_async_generic_fn() {
-    let output = err_context(quote! {async fn f<T>() {}});
+    let output = err_context(quote! {async fn f<T>() -> Result<()> {}});
 
-    assert_err_context_output(output, quote! {async fn f<T>()}, quote! {});
+    assert_err_context_output(output, quote! {async fn f<T>() -> Result<()>}, quote! {});
 }
 
 #[test]
 fn err_context_empty_fn_with_attribute() {
-    let output = err_context(quote! {#[attr] fn f() {}});
+    let output = err_context(quote! {#[attr] fn f() -> Result<()> {}});
 
-    assert_err_context_output(output, quote! {#[attr] fn f()}, quote! {});
+    assert_err_context_output(output, quote! {#[attr] fn f() -> Result<()>}, quote! {});
 }
 
 #[test]
@@ -50,8 +49,313 @@ fn err_context_fn() {
     );
 }
 
+#[test]
+fn err_context_missing_kind() {
+    let err = super::err_context::err_context_impl(quote! {}, quote! {fn f() -> Result<()> {}})
+        .unwrap_err();
+
+    assert!(err.to_string().contains("missing error kind"));
+}
+
+#[test]
+fn err_context_not_a_function() {
+    let err =
+        super::err_context::err_context_impl(quote! {ErrorKind::SomeContext}, quote! {struct S;})
+            .unwrap_err();
+
+    assert!(err.to_string().contains("can only be applied to functions"));
+}
+
+#[test]
+fn err_context_invalid_args() {
+    for attributes in [
+        quote! {function, args(1)},
+        quote! {function, other(a)},
+        quote! {skip, args(a)},
+    ] {
+        let item = quote! {fn f(a: u8) -> Result<()> {}};
+
+        assert!(super::err_context::err_context_impl(attributes, item).is_err());
+    }
+}
+
+#[test]
+fn err_context_function() {
+    let output = super::err_context::err_context_impl(
+        quote! {function, args(a)},
+        quote! {fn f(a: u8) -> Result<()> { g(a) }},
+    )
+    .unwrap()
+    .to_string();
+
+    assert!(output
+        .contains(&quote! {("a", ::infinite_errors::__private::format!("{:?}", a))}.to_string()));
+    assert!(
+        output.contains(&quote! {::core::concat!(::core::module_path!(), "::", "f")}.to_string())
+    );
+    assert!(!output.contains("ErrorContext"));
+}
+
+#[test]
+fn err_context_const_fn() {
+    let err = super::err_context::err_context_impl(
+        quote! {ErrorKind::SomeContext},
+        quote! {const fn f() -> Result<()> {}},
+    )
+    .unwrap_err();
+
+    assert!(err.to_string().contains("`const fn`"));
+}
+
+#[test]
+fn err_context_return_type() {
+    for item in [quote! {fn f() {}}, quote! {fn f() -> u8 {}}] {
+        let err = super::err_context::err_context_impl(quote! {ErrorKind::SomeContext}, item)
+            .unwrap_err();
+
+        assert!(err.to_string().contains("return a `Result`"));
+    }
+
+    for item in [
+        quote! {fn f() -> io::Result<()> {}},
+        quote! {fn f() -> AppResult<()> {}},
+    ] {
+        assert!(
+            super::err_context::err_context_impl(quote! {ErrorKind::SomeContext}, item).is_ok()
+        );
+    }
+}
+
+#[test]
+fn err_context_boxed_future() {
+    let output = err_context(quote! {
+        fn f<'a>(&'a self) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> {
+            Box::pin(async move { Ok(()) })
+        }
+    });
+
+    let result = function_context(
+        quote! {
+            ::infinite_errors::ErrorContext::err_context_with(future.await, move | | ErrorKind::SomeContext)
+        },
+        "f",
+    );
+
+    assert_eq!(
+        output.to_string(),
+        quote! {
+            fn f<'a>(&'a self) -> Pin<Box<dyn Future<Output = Result<()> > + Send + 'a> > {
+                let future = (move | | {
+                    Box::pin(async move { Ok(()) })
+                })();
+                ::infinite_errors::__private::Box::pin(async move {
+                    #result
+                })
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn err_context_impl_block() {
+    let output = err_context(quote! {
+        impl S {
+            fn a(&self) -> Result<()> { a() }
+            #[err_context(ErrorKind::Other)]
+            fn b(&self) -> Result<()> { b() }
+            #[err_context(skip)]
+            fn c(&self) -> Result<()> { c() }
+            fn d(&self) -> u8 { 1 }
+        }
+    });
+
+    let a = function_context(
+        quote! {
+            ::infinite_errors::ErrorContext::err_context_with((move | | { a() })(), move | | ErrorKind::SomeContext)
+        },
+        "S::a",
+    );
+    let b = function_context(
+        quote! {
+            ::infinite_errors::ErrorContext::err_context_with((move | | { b() })(), move | | ErrorKind::Other)
+        },
+        "S::b",
+    );
+
+    assert_eq!(
+        output.to_string(),
+        quote! {
+            impl S {
+                fn a(&self) -> Result<()> {
+                    #a
+                }
+                fn b(&self) -> Result<()> {
+                    #b
+                }
+                fn c(&self) -> Result<()> { c() }
+                fn d(&self) -> u8 { 1 }
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn err_context_borrows_arguments() {
+    let output = super::err_context::err_context_impl(
+        quote! {ErrorKind::Load(path, id), clone(path)},
+        quote! {fn load(path: String, id: u32) -> Result<()> { consume(path) }},
+    )
+    .unwrap();
+
+    let result = function_context(
+        quote! {
+            ::infinite_errors::ErrorContext::err_context_with(
+            (| | { consume(path) })(),
+            | | ErrorKind::Load(__err_context_path, id)
+            )
+        },
+        "load",
+    );
+
+    assert_eq!(
+        output.to_string(),
+        quote! {
+            fn load(path: String, id: u32) -> Result<()> {
+                let __err_context_path = ::core::clone::Clone::clone(&path);
+                #result
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn err_context_borrows_self() {
+    let output = super::err_context::err_context_impl(
+        quote! {ErrorKind::Load(self.id)},
+        quote! {fn bump(&mut self) -> Result<()> { self.count += 1; self.save() }},
+    )
+    .unwrap();
+
+    let result = function_context(
+        quote! {
+            ::infinite_errors::ErrorContext::err_context_with(
+            (| | { self.count += 1; self.save() })(),
+            | | ErrorKind::Load(self.id)
+            )
+        },
+        "bump",
+    );
+
+    assert_eq!(
+        output.to_string(),
+        quote! {
+            fn bump(&mut self) -> Result<()> {
+                #result
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn err_context_clones_arguments() {
+    let output = super::err_context::err_context_impl(
+        quote! {ErrorKind::Load(path), clone(path)},
+        quote! {fn load(path: String) -> Result<()> { consume(path) }},
+    )
+    .unwrap();
+
+    let result = function_context(
+        quote! {
+            ::infinite_errors::ErrorContext::err_context_with(
+            (move | | { consume(path) })(),
+            move | | ErrorKind::Load(__err_context_path)
+            )
+        },
+        "load",
+    );
+
+    assert_eq!(
+        output.to_string(),
+        quote! {
+            fn load(path: String) -> Result<()> {
+                let __err_context_path = ::core::clone::Clone::clone(&path);
+                #result
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn err_context_clone_not_used() {
+    let err = super::err_context::err_context_impl(
+        quote! {ErrorKind::Load, clone(path)},
+        quote! {fn load(path: String) -> Result<()> { consume(path) }},
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "`path` is not used by the error kind or `args`"
+    );
+}
+
+#[test]
+fn err_context_future_borrows_arguments() {
+    let err = super::err_context::err_context_impl(
+        quote! {ErrorKind::Load(path)},
+        quote! {fn load(path: String) -> impl Future<Output = Result<()>> { async move { consume(path) } }},
+    )
+    .unwrap_err();
+
+    assert!(err.to_string().contains("`clone(...)`"));
+}
+
+#[test]
+fn err_context_with_function() {
+    let output = super::err_context::err_context_impl(
+        quote! {with = make_kind},
+        quote! {fn f() -> Result<()> { g() }},
+    )
+    .unwrap();
+
+    let result = function_context(
+        quote! {
+            ::infinite_errors::ErrorContext::err_context_with((move | | { g() })(), make_kind)
+        },
+        "f",
+    );
+
+    assert_eq!(
+        output.to_string(),
+        quote! {
+            fn f() -> Result<()> {
+                #result
+            }
+        }
+        .to_string()
+    );
+}
+
+/// Wrap the expected result of a function with a kind in the function frame
+/// recorded with `INFINITE_ERRORS_FUNCTION_CONTEXT`.
+fn function_context(result: proc_macro2::TokenStream, name: &str) -> proc_macro2::TokenStream {
+    quote! {
+        ::infinite_errors::__private::function_context(
+            #result,
+            ::core::option_env!("INFINITE_ERRORS_FUNCTION_CONTEXT"),
+            ::core::concat!(::core::module_path!(), "::", #name),
+            ::core::panic::Location::caller(),
+        )
+    }
+}
+
 fn err_context(item: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
-    super::err_context_impl(quote! {ErrorKind::SomeContext}, item)
+    super::err_context::err_context_impl(quote! {ErrorKind::SomeContext}, item).unwrap()
 }
 
 fn assert_err_context_output(
@@ -59,23 +363,356 @@ fn assert_err_context_output(
     decl: proc_macro2::TokenStream,
     body: proc_macro2::TokenStream,
 ) {
-    let parsed_decl: ItemFn = parse_quote! {#decl {}};
+    let parsed_decl: syn::ItemFn = syn::parse_quote! {#decl {}};
+    let name = parsed_decl.sig.ident.to_string();
     let (left, right) = if parsed_decl.sig.asyncness.is_some() {
         (quote! {async move}, quote! {.await})
     } else {
         (quote! {move | |}, quote! {()})
     };
 
+    let result = function_context(
+        quote! {
+            ::infinite_errors::ErrorContext::err_context_with(
+                (#left {
+                    #body
+                })#right,
+                move | | ErrorKind::SomeContext
+            )
+        },
+        &name,
+    );
+
     assert_eq!(
         output.to_string(),
         quote! {
                 #decl {
-                ::infinite_errors::ErrorContext::err_context(
-                    (#left {
-                        #body
-                    })#right,
-                    ErrorKind::SomeContext
-                )
+                #result
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn error_type_defaults() {
+    let output = error_type(quote! {kind = ErrorKind}, quote! {pub struct AppError;});
+
+    assert_eq!(
+        output.to_string(),
+        quote! {
+            ::infinite_errors::__declare_error_type! {
+                pub struct AppError;
+                kind = ErrorKind;
+                context = ErrorContext;
+                source_context = SourceContext;
+                option_context = OptionContext;
+                future_context = FutureErrorContext;
+                stream_context = TryStreamErrorContext;
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn error_type_all_arguments() {
+    let output = error_type(
+        quote! {kind = crate::kinds::Kind<u8>, context = Ctx, source_context = SrcCtx, option_context = OptCtx, result = AppResult},
+        quote! {#[doc = "My error."] pub(crate) struct AppError;},
+    );
+
+    assert_eq!(
+        output.to_string(),
+        quote! {
+            ::infinite_errors::__declare_error_type! {
+                #[doc = "My error."]
+                pub(crate) struct AppError;
+                kind = crate::kinds::Kind<u8>;
+                context = Ctx;
+                source_context = SrcCtx;
+                option_context = OptCtx;
+                future_context = FutureErrorContext;
+                stream_context = TryStreamErrorContext;
+                result = AppResult;
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn error_type_missing_kind() {
+    let output = error_type(quote! {}, quote! {struct AppError;});
+
+    assert!(output.to_string().contains("missing error kind"));
+}
+
+#[test]
+fn error_type_unknown_argument() {
+    let output = error_type(quote! {kind = K, name = Other}, quote! {struct AppError;});
+
+    assert!(output.to_string().contains("unknown argument"));
+}
+
+#[test]
+fn error_type_derives() {
+    let output = error_type(
+        quote! {kind = K},
+        quote! {#[derive(Debug, Clone, other::Derive)] #[derive(std::fmt::Debug)] struct AppError;},
+    );
+
+    assert_eq!(
+        output.to_string(),
+        quote! {
+            ::infinite_errors::__declare_error_type! {
+                #[derive(Clone, other::Derive)]
+                struct AppError;
+                kind = K;
+                context = ErrorContext;
+                source_context = SourceContext;
+                option_context = OptionContext;
+                future_context = FutureErrorContext;
+                stream_context = TryStreamErrorContext;
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn error_type_unsupported_derive() {
+    let output = error_type(
+        quote! {kind = K},
+        quote! {#[derive(Debug, Clone, PartialEq)] struct AppError;},
+    );
+
+    assert!(output
+        .to_string()
+        .contains("`PartialEq` cannot be derived for an error type"));
+}
+
+#[test]
+fn error_type_prefix() {
+    let output = error_type(
+        quote! {kind = K, prefix = Db, option_context = DbOption},
+        quote! {struct DbError;},
+    );
+
+    assert_eq!(
+        output.to_string(),
+        quote! {
+            ::infinite_errors::__declare_error_type! {
+                struct DbError;
+                kind = K;
+                context = DbErrorContext;
+                source_context = DbSourceContext;
+                option_context = DbOption;
+                future_context = DbFutureErrorContext;
+                stream_context = DbTryStreamErrorContext;
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn error_type_struct_with_fields() {
+    let output = error_type(quote! {kind = K}, quote! {struct AppError { x: u8 }});
+
+    assert!(output.to_string().contains("unit struct"));
+}
+
+fn error_type(
+    attributes: proc_macro2::TokenStream,
+    item: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    super::error_type::error_type_impl(attributes, item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+}
+
+#[test]
+fn error_kind_rewrite_positional_fields() {
+    use super::error_kind::rewrite_positional_fields;
+
+    assert_eq!(rewrite_positional_fields("{0} {1:?}"), "{_0} {_1:?}");
+    assert_eq!(rewrite_positional_fields("{{0}} {name}"), "{{0}} {name}");
+    assert_eq!(rewrite_positional_fields("{} {:>4}"), "{} {:>4}");
+}
+
+#[test]
+fn error_kind_display_and_from() {
+    let output = super::error_kind::error_kind_impl(quote! {
+        enum Kind {
+            #[error("a {0}")]
+            A(#[from] u8),
+            #[error("b {name}", name = 1)]
+            #[kind(code = "B", severity = Warning, status = 404, public)]
+            #[kind(problem_type = "https://example.com/b", title = "B")]
+            B { name: u8 },
+            #[error(transparent)]
+            #[kind(status = 503, grpc = Unavailable)]
+            C(u16),
+        }
+    })
+    .unwrap();
+
+    assert_eq!(
+        output.to_string(),
+        quote! {
+            impl ::core::fmt::Display for Kind {
+                fn fmt(&self, __formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                    match self {
+                        #[allow(unused_variables)]
+                        Self::A(_0) => ::core::write!(__formatter, "a {_0}"),
+                        #[allow(unused_variables)]
+                        Self::B { name } => ::core::write!(__formatter, "b {name}", name = 1),
+                        #[allow(unused_variables)]
+                        Self::C(_0) => ::core::fmt::Display::fmt(_0, __formatter),
+                    }
+                }
+            }
+
+            impl ::infinite_errors::KindMetadata for Kind {
+                fn code(&self) -> ::core::option::Option<&'static str> {
+                    match self {
+                        Self::A(..) => ::core::option::Option::None,
+                        Self::B { .. } => ::core::option::Option::Some("B"),
+                        Self::C(..) => ::core::option::Option::None,
+                    }
+                }
+
+                fn severity(&self) -> ::infinite_errors::Severity {
+                    match self {
+                        Self::A(..) => ::infinite_errors::Severity::Error,
+                        Self::B { .. } => ::infinite_errors::Severity::Warning,
+                        Self::C(..) => ::infinite_errors::Severity::Error,
+                    }
+                }
+            }
+
+            impl ::infinite_errors::ErrorStatus for Kind {
+                fn http_status(&self) -> u16 {
+                    match self {
+                        Self::A(..) => 500,
+                        Self::B { .. } => 404,
+                        Self::C(..) => 503,
+                    }
+                }
+
+                fn grpc_code(&self) -> ::infinite_errors::GrpcCode {
+                    match self {
+                        Self::A(..) => ::infinite_errors::GrpcCode::from_http_status(500),
+                        Self::B { .. } => ::infinite_errors::GrpcCode::from_http_status(404),
+                        Self::C(..) => ::infinite_errors::GrpcCode::Unavailable,
+                    }
+                }
+
+                fn is_public(&self) -> bool {
+                    match self {
+                        Self::A(..) => false,
+                        Self::B { .. } => true,
+                        Self::C(..) => false,
+                    }
+                }
+
+                fn problem_type(&self) -> &'static str {
+                    match self {
+                        Self::A(..) => "about:blank",
+                        Self::B { .. } => "https://example.com/b",
+                        Self::C(..) => "about:blank",
+                    }
+                }
+
+                fn title(&self) -> ::core::option::Option<&'static str> {
+                    match self {
+                        Self::A(..) => ::core::option::Option::None,
+                        Self::B { .. } => ::core::option::Option::Some("B"),
+                        Self::C(..) => ::core::option::Option::None,
+                    }
+                }
+            }
+
+            impl ::core::convert::From<u8> for Kind {
+                fn from(source: u8) -> Self {
+                    Self::A { 0: source }
+                }
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn error_kind_without_status() {
+    let output = super::error_kind::error_kind_impl(quote! {
+        #[kind(code = "A")]
+        enum Kind {
+            #[error("a")]
+            A,
+        }
+    })
+    .unwrap();
+
+    assert!(!output.to_string().contains("ErrorStatus"));
+}
+
+#[test]
+fn error_kind_default_status() {
+    let output = super::error_kind::error_kind_impl(quote! {
+        #[kind(status)]
+        enum Kind {
+            #[error("a")]
+            A,
+        }
+    })
+    .unwrap();
+
+    assert!(output.to_string().contains("ErrorStatus"));
+    assert!(output.to_string().contains("Self :: A => 500"));
+}
+
+#[test]
+fn error_kind_missing_display() {
+    let err = super::error_kind::error_kind_impl(quote! {
+        enum Kind {
+            A,
+        }
+    })
+    .unwrap_err();
+
+    assert!(err.to_string().contains("missing `#[error"));
+}
+
+#[test]
+fn err_context_formats_args_on_error() {
+    let output = super::err_context::err_context_impl(
+        quote! {function, args(id)},
+        quote! {fn load(id: u32) -> Result<()> { g(id) }},
+    )
+    .unwrap();
+
+    assert_eq!(
+        output.to_string(),
+        quote! {
+            fn load(id: u32) -> Result<()> {
+                let __err_context_location = ::core::panic::Location::caller();
+                match (| | { g(id) })() {
+                    ::core::result::Result::Ok(x) => ::core::result::Result::Ok(x),
+                    ::core::result::Result::Err(x) => {
+                        let function = ::infinite_errors::FunctionFrame::new(
+                            ::core::concat!(::core::module_path!(), "::", "load"),
+                            __err_context_location,
+                            ::infinite_errors::__private::vec![
+                                ("id", ::infinite_errors::__private::format!("{:?}", id))
+                            ],
+                        );
+                        ::infinite_errors::FunctionContext::function_context(
+                            ::core::result::Result::Err(::core::convert::Into::into(x)),
+                            move | | function
+                        )
+                    }
+                }
             }
         }
         .to_string()
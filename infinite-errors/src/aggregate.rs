@@ -0,0 +1,86 @@
+//! Support for errors with more than one cause.
+
+use alloc::vec::Vec;
+use core::{fmt, ops::Deref};
+
+/// A non-empty list of errors collected by [CollectErrors::collect_errors].
+///
+/// The `ErrorContext` trait generated by [declare_error_type](crate::declare_error_type)
+/// is implemented for `Result<T, Errors<Error>>`, wrapping every error in a
+/// single context frame.
+#[derive(Debug)]
+pub struct Errors<E>(Vec<E>);
+
+impl<E> Errors<E> {
+    /// Get the inner list of errors.
+    pub fn into_inner(self) -> Vec<E> {
+        self.0
+    }
+}
+
+impl<E> Deref for Errors<E> {
+    type Target = [E];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> IntoIterator for Errors<E> {
+    type Item = E;
+    type IntoIter = alloc::vec::IntoIter<E>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<E> fmt::Display for Errors<E>
+where
+    E: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut errors = self.0.iter();
+        if let Some(first) = errors.next() {
+            write!(f, "{first}")?;
+        }
+        for err in errors {
+            write!(f, "; {err}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Iterator adapter to collect every error of an iterator of [Result]s.
+pub trait CollectErrors<T, E>: Iterator<Item = Result<T, E>> {
+    /// Collect all the successful values, or all the errors if at least one
+    /// item is an error.
+    ///
+    /// Unlike collecting into a `Result<Vec<T>, E>`, this does not stop at
+    /// the first error.
+    fn collect_errors(self) -> Result<Vec<T>, Errors<E>>;
+}
+
+impl<T, E, I> CollectErrors<T, E> for I
+where
+    I: Iterator<Item = Result<T, E>>,
+{
+    fn collect_errors(self) -> Result<Vec<T>, Errors<E>> {
+        let mut values = Vec::new();
+        let mut errors = Vec::new();
+        for item in self {
+            match item {
+                Ok(value) if errors.is_empty() => values.push(value),
+                Ok(_) => {}
+                Err(err) => errors.push(err),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(values)
+        } else {
+            Err(Errors(errors))
+        }
+    }
+}
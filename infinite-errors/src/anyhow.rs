@@ -0,0 +1,30 @@
+//! Interoperability with [anyhow].
+//!
+//! With the `anyhow` feature, the `ErrorContext` trait generated by
+//! [declare_error_type](crate::declare_error_type) also works on
+//! [anyhow::Result], turning it into a result of the generated error type. The
+//! layers of the chain of the `anyhow` error become [ContextFrame]s, kept as
+//! the foreign source of the new frame, so its whole chain of contexts is
+//! still reported. The generated `SourceContext` trait works on
+//! [anyhow::Result] as well.
+//!
+//! The kind must not convert from [anyhow::Error], through a `#[from]` variant
+//! for instance: `ErrorContext` is also implemented for every `Result<T, E>`
+//! whose error converts into the error type, and that implementation would
+//! then overlap with the one for [anyhow::Result]. Keep `anyhow` errors as
+//! sources with `SourceContext` instead.
+//!
+//! The other way around, [IntoAnyhow] turns an error chain into an
+//! [anyhow::Error], keeping every frame as a [ContextFrame].
+
+#[doc(no_inline)]
+pub use ::anyhow::Error;
+
+pub use crate::interop::ContextFrame;
+
+crate::interop::foreign_module!(
+    anyhow::Error,
+    from_anyhow,
+    IntoAnyhow::into_anyhow,
+    "`anyhow` error"
+);
@@ -0,0 +1,136 @@
+//! Typed key-value attachments on error frames.
+
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use core::{any::Any, fmt};
+
+use crate::ErrorType;
+
+/// Value which can be attached to an error frame.
+///
+/// Implemented for every type which is [fmt::Debug], [Send], [Sync] and
+/// `'static`.
+pub trait AttachmentValue: Any + fmt::Debug + Send + Sync {
+    /// Get this value as [Any], to downcast it.
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T> AttachmentValue for T
+where
+    T: Any + fmt::Debug + Send + Sync,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// Shared, so that cloning an error doesn't clone its attached values.
+type Entry = (&'static str, Arc<dyn AttachmentValue>);
+
+/// Set of key-value attachments of a single error frame.
+///
+/// Keys are unique: attaching a value with an existing key replaces the
+/// previous value.
+#[derive(Clone, Default)]
+pub struct Attachments {
+    // Boxed to keep errors small, since most frames have no attachments.
+    #[allow(clippy::box_collection)]
+    entries: Option<Box<Vec<Entry>>>,
+}
+
+impl Attachments {
+    /// Attach a value with the given key.
+    pub fn insert(&mut self, key: &'static str, value: impl AttachmentValue) {
+        let value = Arc::new(value);
+        let entries = self.entries.get_or_insert_with(Default::default);
+        match entries.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => *v = value,
+            None => entries.push((key, value)),
+        }
+    }
+
+    /// Get the value attached with the given key, if one exists.
+    pub fn get_by_key(&self, key: &str) -> Option<&dyn AttachmentValue> {
+        self.entries()
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v.as_ref())
+    }
+
+    /// Get the value attached with the given key if it is of type `T`.
+    pub fn get<T>(&self, key: &str) -> Option<&T>
+    where
+        T: Any,
+    {
+        self.get_by_key(key)?.as_any().downcast_ref()
+    }
+
+    /// Get the first attached value of type `T`, if one exists.
+    pub fn get_by_type<T>(&self) -> Option<&T>
+    where
+        T: Any,
+    {
+        self.entries()
+            .iter()
+            .find_map(|(_, v)| v.as_ref().as_any().downcast_ref())
+    }
+
+    /// Iterate over the keys and values, in the order they were attached.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &dyn AttachmentValue)> {
+        self.entries().iter().map(|(k, v)| (*k, v.as_ref()))
+    }
+
+    /// Get the number of attachments.
+    pub fn len(&self) -> usize {
+        self.entries().len()
+    }
+
+    /// Check whether there are no attachments.
+    pub fn is_empty(&self) -> bool {
+        self.entries().is_empty()
+    }
+
+    fn entries(&self) -> &[Entry] {
+        self.entries.as_deref().map_or(&[], Vec::as_slice)
+    }
+}
+
+impl fmt::Debug for Attachments {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Helper trait to attach values to the outermost frame of errors.
+pub trait Attach<T, E> {
+    /// Attach a value with the given key to the outermost frame of the error.
+    fn attach(self, key: &'static str, value: impl AttachmentValue) -> Result<T, E>;
+
+    /// Attach a value returned by a function with the given key to the
+    /// outermost frame of the error. The function should only be called if
+    /// `self` is indeed an error.
+    fn attach_with<V>(self, key: &'static str, value: impl FnOnce() -> V) -> Result<T, E>
+    where
+        V: AttachmentValue;
+}
+
+impl<T, E> Attach<T, E> for Result<T, E>
+where
+    E: ErrorType,
+{
+    fn attach(self, key: &'static str, value: impl AttachmentValue) -> Result<T, E> {
+        self.map_err(|mut err| {
+            err.attachments_mut().insert(key, value);
+            err
+        })
+    }
+
+    fn attach_with<V>(self, key: &'static str, value: impl FnOnce() -> V) -> Result<T, E>
+    where
+        V: AttachmentValue,
+    {
+        self.map_err(|mut err| {
+            err.attachments_mut().insert(key, value());
+            err
+        })
+    }
+}
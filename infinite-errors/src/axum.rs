@@ -0,0 +1,56 @@
+//! HTTP responses for error types, with `axum`.
+//!
+//! With the `axum` feature, [declare_error_type](crate::declare_error_type)
+//! also implements [IntoResponse] for the generated error type when its kind
+//! implements [ErrorStatus] and [Display](std::fmt::Display). The response
+//! has the status of the outermost frame and a JSON [ErrorBody].
+//!
+//! [ProblemDetails] implements [IntoResponse] too, with the
+//! `application/problem+json` content type.
+
+use std::fmt;
+
+#[doc(no_inline)]
+pub use ::axum_core::response::{IntoResponse, Response};
+use ::http::{header, HeaderValue, StatusCode};
+
+use crate::{ErrorBody, ErrorStatus, ErrorType, ProblemDetails};
+
+/// Build the HTTP response of an error.
+pub fn into_response<E>(err: &E) -> Response
+where
+    E: ErrorType,
+    E::ErrorKind: ErrorStatus + fmt::Display,
+{
+    let body = ErrorBody::new(err);
+    let status = body.status_code();
+    let body = serde_json::to_vec(&body).expect("an error body can always be serialized");
+
+    (
+        status,
+        [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        )],
+        body,
+    )
+        .into_response()
+}
+
+impl IntoResponse for ProblemDetails {
+    fn into_response(self) -> Response {
+        let status =
+            StatusCode::from_u16(self.status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let body = serde_json::to_vec(&self).expect("problem details can always be serialized");
+
+        (
+            status,
+            [(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/problem+json"),
+            )],
+            body,
+        )
+            .into_response()
+    }
+}
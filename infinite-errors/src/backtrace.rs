@@ -0,0 +1,48 @@
+//! Optional capture of runtime backtraces.
+
+#[cfg(feature = "std")]
+use std::backtrace::Backtrace;
+#[cfg(feature = "backtrace")]
+use std::backtrace::BacktraceStatus;
+#[cfg(feature = "backtrace")]
+use std::sync::Arc;
+
+/// Backtrace captured where the innermost frame of an error was created.
+///
+/// Only captured when the `backtrace` feature is enabled, following the
+/// same environment variables as [Backtrace::capture]: `RUST_LIB_BACKTRACE`
+/// and `RUST_BACKTRACE`. Otherwise this type is empty.
+///
+/// The backtrace can only be read with the `std` feature. Clones share the
+/// same backtrace.
+#[derive(Clone, Debug, Default)]
+pub struct OriginBacktrace {
+    #[cfg(feature = "backtrace")]
+    inner: Option<Arc<Backtrace>>,
+}
+
+impl OriginBacktrace {
+    /// Capture a backtrace of the current thread, if enabled.
+    #[cfg_attr(not(feature = "backtrace"), inline(always))]
+    pub fn capture() -> Self {
+        #[cfg(feature = "backtrace")]
+        {
+            let backtrace = Backtrace::capture();
+            Self {
+                inner: (backtrace.status() == BacktraceStatus::Captured)
+                    .then(|| Arc::new(backtrace)),
+            }
+        }
+        #[cfg(not(feature = "backtrace"))]
+        Self {}
+    }
+
+    /// Get the captured backtrace, if one exists.
+    #[cfg(feature = "std")]
+    pub fn get(&self) -> Option<&Backtrace> {
+        #[cfg(feature = "backtrace")]
+        return self.inner.as_deref();
+        #[cfg(not(feature = "backtrace"))]
+        None
+    }
+}
@@ -0,0 +1,109 @@
+//! Conversion of error chains between error types.
+
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use core::{error::Error as StdError, panic::Location};
+
+use crate::{Attachments, ErrorType, FunctionFrame, OriginBacktrace, OriginTrace};
+
+/// All the data of a single frame, as returned by [ErrorType::into_parts].
+pub struct Parts<E>
+where
+    E: ErrorType,
+{
+    /// Error kind of the frame.
+    pub kind: E::ErrorKind,
+    /// Causes of the frame.
+    pub causes: Vec<E>,
+    /// Foreign error which caused the frame.
+    pub source: Option<Arc<dyn StdError + Send + Sync>>,
+    /// Location where the frame was constructed.
+    pub location: &'static Location<'static>,
+    /// Runtime backtrace captured with the frame.
+    pub backtrace: OriginBacktrace,
+    /// Trace context captured with the frame.
+    pub trace: OriginTrace,
+    /// Key-value attachments of the frame.
+    pub attachments: Attachments,
+    /// Functions the frame went through, innermost first.
+    pub functions: Vec<FunctionFrame>,
+}
+
+/// What [ErrorType::convert_chain] does with a frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KindMapping<K> {
+    /// Keep the frame with the given kind, and convert its causes.
+    Map(K),
+    /// Nest the frame, along with all of its causes, as the foreign source of
+    /// a new frame with the given kind. The new frame keeps the location of
+    /// the nested one.
+    Nest(K),
+}
+
+pub(crate) fn map_kinds<A, B>(err: A, f: &mut dyn FnMut(A::ErrorKind) -> B::ErrorKind) -> B
+where
+    A: ErrorType,
+    B: ErrorType,
+{
+    let Parts {
+        kind,
+        causes,
+        source,
+        location,
+        backtrace,
+        trace,
+        attachments,
+        functions,
+    } = err.into_parts();
+    let kind = f(kind);
+    let causes = causes.into_iter().map(|x| map_kinds(x, f)).collect();
+
+    B::from_parts(Parts {
+        kind,
+        causes,
+        source,
+        location,
+        backtrace,
+        trace,
+        attachments,
+        functions,
+    })
+}
+
+pub(crate) fn convert_chain<A, B>(
+    err: A,
+    f: &mut dyn FnMut(&A::ErrorKind) -> KindMapping<B::ErrorKind>,
+) -> B
+where
+    A: ErrorType + StdError + Send + Sync + 'static,
+    B: ErrorType,
+{
+    let kind = match f(err.kind()) {
+        KindMapping::Map(kind) => kind,
+        KindMapping::Nest(kind) => {
+            let location = err.location();
+            return B::with_foreign_source(kind, Box::new(err), location);
+        }
+    };
+    let Parts {
+        causes,
+        source,
+        location,
+        backtrace,
+        trace,
+        attachments,
+        functions,
+        ..
+    } = err.into_parts();
+    let causes = causes.into_iter().map(|x| convert_chain(x, f)).collect();
+
+    B::from_parts(Parts {
+        kind,
+        causes,
+        source,
+        location,
+        backtrace,
+        trace,
+        attachments,
+        functions,
+    })
+}
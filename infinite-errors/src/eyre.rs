@@ -0,0 +1,30 @@
+//! Interoperability with [eyre].
+//!
+//! With the `eyre` feature, the `ErrorContext` trait generated by
+//! [declare_error_type](crate::declare_error_type) also works on
+//! [eyre::Result], turning it into a result of the generated error type. The
+//! layers of the chain of the `eyre` report become [ContextFrame]s, kept as
+//! the foreign source of the new frame, so its whole chain of contexts is
+//! still reported. The generated `SourceContext` trait works on
+//! [eyre::Result] as well.
+//!
+//! The kind must not convert from [eyre::Report], through a `#[from]` variant
+//! for instance: `ErrorContext` is also implemented for every `Result<T, E>`
+//! whose error converts into the error type, and that implementation would
+//! then overlap with the one for [eyre::Result]. Keep `eyre` reports as
+//! sources with `SourceContext` instead.
+//!
+//! The other way around, [IntoEyre] turns an error chain into an
+//! [eyre::Report], keeping every frame as a [ContextFrame].
+
+#[doc(no_inline)]
+pub use ::eyre::Report;
+
+pub use crate::interop::ContextFrame;
+
+crate::interop::foreign_module!(
+    eyre::Report,
+    from_eyre,
+    IntoEyre::into_eyre,
+    "`eyre` report"
+);
@@ -0,0 +1,93 @@
+//! Stable fingerprints of error chains.
+
+use core::{
+    fmt::{self, Write},
+    hash::Hasher,
+};
+
+use crate::{ErrorType, VariantName};
+
+/// Fingerprint of the failure path of an error chain.
+///
+/// Only the shape of the chain, the variant name of each kind and the file
+/// and line of each [Location](std::panic::Location) are hashed. Field
+/// values, messages, columns and foreign sources are left out, so the same
+/// failure path always gets the same fingerprint, across runs, platforms and
+/// compiler releases, and across edits of other files. The variant name is
+/// displayed by [VariantName], so renaming a variant changes it, but
+/// reordering the variants does not.
+///
+/// As line numbers are hashed, any edit moving a frame to another line, such
+/// as adding a line above it in the same file, or moving its file, changes
+/// the fingerprint of every chain going through it.
+///
+/// This relies on the [Debug](fmt::Debug) implementation of the kind
+/// starting with the variant name, as derived ones do. With a hand-written
+/// one which doesn't, such as one printing `Error` for every variant or one
+/// starting with a shared prefix like `failed to ...`, different variants get
+/// the same fingerprint.
+///
+/// Displayed as 16 hexadecimal digits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fingerprint(u64);
+
+impl Fingerprint {
+    /// Compute the fingerprint of an error chain.
+    pub fn new<E>(err: &E) -> Self
+    where
+        E: ErrorType,
+        E::ErrorKind: fmt::Debug,
+    {
+        let mut hasher = Fnv1a::default();
+        for frame in err.chain() {
+            let _ = write!(hasher, "{}", VariantName(frame.kind()));
+            hasher.write_u8(0xff);
+            hasher.write(frame.location().file().as_bytes());
+            hasher.write(&frame.location().line().to_le_bytes());
+            hasher.write(&(frame.causes().len() as u64).to_le_bytes());
+        }
+
+        Self(hasher.finish())
+    }
+
+    /// Get the value of this fingerprint.
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// 64-bit FNV-1a, which unlike the hasher of the standard library is
+/// guaranteed to give the same result on every release.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+impl Write for Fnv1a {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        Hasher::write(self, s.as_bytes());
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+//! Function frames recorded by `err_context`.
+
+use alloc::{string::String, vec::Vec};
+use core::panic::Location;
+
+use crate::ErrorType;
+
+/// Function an error went through, recorded by `err_context`.
+///
+/// Despite its name, it is not a frame of the error chain: it has no kind and
+/// does not show up in the display string or the fingerprint of the error. It
+/// is kept on the outermost frame of the chain when the function returns,
+/// see [ErrorType::functions], and shown by the [Report](crate::Report).
+#[derive(Clone, Debug)]
+pub struct FunctionFrame {
+    name: &'static str,
+    location: &'static Location<'static>,
+    args: Vec<(&'static str, String)>,
+}
+
+impl FunctionFrame {
+    /// Create a new function frame.
+    pub fn new(
+        name: &'static str,
+        location: &'static Location<'static>,
+        args: Vec<(&'static str, String)>,
+    ) -> Self {
+        Self {
+            name,
+            location,
+            args,
+        }
+    }
+
+    /// Get the fully qualified name of the function.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Get the location of the function.
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+    /// Get the names and debug strings of the arguments of the function
+    /// selected with `args(...)`, formatted when it returned the error. An
+    /// argument the body mutated through a borrow shows its value at that
+    /// point, not the one it was called with.
+    pub fn args(&self) -> &[(&'static str, String)] {
+        &self.args
+    }
+}
+
+/// Helper trait to record the functions an error goes through.
+pub trait FunctionContext<T, E> {
+    /// Record a function frame, returned by a function, on the outermost
+    /// frame of the error. The function should only be called if `self` is
+    /// indeed an error.
+    fn function_context(self, frame: impl FnOnce() -> FunctionFrame) -> Result<T, E>;
+}
+
+impl<T, E> FunctionContext<T, E> for Result<T, E>
+where
+    E: ErrorType,
+{
+    fn function_context(self, frame: impl FnOnce() -> FunctionFrame) -> Result<T, E> {
+        self.map_err(|mut err| {
+            err.functions_mut().push(frame());
+            err
+        })
+    }
+}
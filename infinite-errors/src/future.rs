@@ -0,0 +1,221 @@
+//! Context for futures and streams of results.
+//!
+//! The types in this module are returned by the `FutureErrorContext` and
+//! `TryStreamErrorContext` traits generated by
+//! [declare_error_type](crate::declare_error_type).
+
+use alloc::vec;
+use core::{
+    future::Future,
+    marker::PhantomData,
+    panic::Location,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+#[doc(no_inline)]
+pub use futures_core::{Stream, TryStream};
+use pin_project_lite::pin_project;
+
+use crate::ErrorType;
+
+pin_project! {
+    /// Future adding a context of the given kind to the error it outputs,
+    /// converted into the error type `E`.
+    #[derive(Debug)]
+    #[must_use = "futures do nothing unless you `.await` or poll them"]
+    pub struct ErrContext<Fut, K, E> {
+        #[pin]
+        future: Fut,
+        kind: Option<K>,
+        location: &'static Location<'static>,
+        error: PhantomData<fn() -> E>,
+    }
+}
+
+impl<Fut, K, E> ErrContext<Fut, K, E> {
+    /// Create a new [ErrContext], recording the given location in the new
+    /// frame.
+    pub fn new(future: Fut, kind: K, location: &'static Location<'static>) -> Self {
+        Self {
+            future,
+            kind: Some(kind),
+            location,
+            error: PhantomData,
+        }
+    }
+}
+
+impl<Fut, T, OE, E, K> Future for ErrContext<Fut, K, E>
+where
+    Fut: Future<Output = Result<T, OE>>,
+    OE: Into<E>,
+    E: ErrorType<ErrorKind = K>,
+{
+    type Output = Result<T, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let result = ready!(this.future.poll(cx));
+
+        Poll::Ready(result.map_err(|err| {
+            let kind = this
+                .kind
+                .take()
+                .expect("`ErrContext` polled after completion");
+            E::with_causes(kind, vec![err.into()], this.location)
+        }))
+    }
+}
+
+pin_project! {
+    /// Future adding a context of the kind returned by a function to the error
+    /// it outputs, converted into the error type `E`. The function is only
+    /// called if there is an error.
+    #[derive(Debug)]
+    #[must_use = "futures do nothing unless you `.await` or poll them"]
+    pub struct ErrContextWith<Fut, F, E> {
+        #[pin]
+        future: Fut,
+        kind: Option<F>,
+        location: &'static Location<'static>,
+        error: PhantomData<fn() -> E>,
+    }
+}
+
+impl<Fut, F, E> ErrContextWith<Fut, F, E> {
+    /// Create a new [ErrContextWith], recording the given location in the new
+    /// frame.
+    pub fn new(future: Fut, kind: F, location: &'static Location<'static>) -> Self {
+        Self {
+            future,
+            kind: Some(kind),
+            location,
+            error: PhantomData,
+        }
+    }
+}
+
+impl<Fut, F, T, OE, E> Future for ErrContextWith<Fut, F, E>
+where
+    Fut: Future<Output = Result<T, OE>>,
+    OE: Into<E>,
+    E: ErrorType,
+    F: FnOnce() -> E::ErrorKind,
+{
+    type Output = Result<T, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let result = ready!(this.future.poll(cx));
+
+        Poll::Ready(result.map_err(|err| {
+            let kind = this
+                .kind
+                .take()
+                .expect("`ErrContextWith` polled after completion");
+            E::with_causes(kind(), vec![err.into()], this.location)
+        }))
+    }
+}
+
+pin_project! {
+    /// Stream adding a context of the given kind to every error it yields,
+    /// converted into the error type `E`.
+    #[derive(Debug)]
+    #[must_use = "streams do nothing unless polled"]
+    pub struct StreamErrContext<St, K, E> {
+        #[pin]
+        stream: St,
+        kind: K,
+        location: &'static Location<'static>,
+        error: PhantomData<fn() -> E>,
+    }
+}
+
+impl<St, K, E> StreamErrContext<St, K, E> {
+    /// Create a new [StreamErrContext], recording the given location in the
+    /// new frames.
+    pub fn new(stream: St, kind: K, location: &'static Location<'static>) -> Self {
+        Self {
+            stream,
+            kind,
+            location,
+            error: PhantomData,
+        }
+    }
+}
+
+impl<St, K, E> Stream for StreamErrContext<St, K, E>
+where
+    St: TryStream,
+    St::Error: Into<E>,
+    E: ErrorType<ErrorKind = K>,
+    K: Clone,
+{
+    type Item = Result<St::Ok, E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let item = ready!(this.stream.try_poll_next(cx));
+
+        Poll::Ready(item.map(|result| {
+            result.map_err(|err| E::with_causes(this.kind.clone(), vec![err.into()], this.location))
+        }))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
+
+pin_project! {
+    /// Stream adding a context of the kind returned by a function to every
+    /// error it yields, converted into the error type `E`. The function is
+    /// only called for errors.
+    #[derive(Debug)]
+    #[must_use = "streams do nothing unless polled"]
+    pub struct StreamErrContextWith<St, F, E> {
+        #[pin]
+        stream: St,
+        kind: F,
+        location: &'static Location<'static>,
+        error: PhantomData<fn() -> E>,
+    }
+}
+
+impl<St, F, E> StreamErrContextWith<St, F, E> {
+    /// Create a new [StreamErrContextWith], recording the given location in
+    /// the new frames.
+    pub fn new(stream: St, kind: F, location: &'static Location<'static>) -> Self {
+        Self {
+            stream,
+            kind,
+            location,
+            error: PhantomData,
+        }
+    }
+}
+
+impl<St, F, E> Stream for StreamErrContextWith<St, F, E>
+where
+    St: TryStream,
+    St::Error: Into<E>,
+    E: ErrorType,
+    F: FnMut() -> E::ErrorKind,
+{
+    type Item = Result<St::Ok, E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let item = ready!(this.stream.try_poll_next(cx));
+
+        Poll::Ready(item.map(|result| {
+            result.map_err(|err| E::with_causes((this.kind)(), vec![err.into()], this.location))
+        }))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
@@ -0,0 +1,166 @@
+//! Process-wide hooks run whenever an error is created.
+//!
+//! Hooks see every frame created by an error type generated by
+//! [declare_error_type](crate::declare_error_type): through `Error::new`,
+//! [From], the generated context traits or `Error::with_source`, whether the
+//! error ends up logged or not. They are meant for telemetry, such as
+//! counting or sampling errors:
+//!
+//! ```ignore
+//! infinite_errors::hook::add_hook(|event| {
+//!     metrics::counter!("errors", "kind" => event.variant_name().to_string()).increment(1);
+//! });
+//! ```
+//!
+//! This module requires the `hooks` feature, which enables `std`: hooks are
+//! stored in a `RwLock`, and a `thread_local!` keeps them from running again
+//! for errors they create. Hooks do not require anything more from error
+//! kinds than the generated error types already do: kinds are only formatted
+//! through [Debug](fmt::Debug), by [ErrorEvent::variant_name]. Without any
+//! hook installed, creating an error only costs an atomic load.
+
+use std::{
+    any::Any,
+    cell::Cell,
+    fmt,
+    panic::Location,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+};
+
+use crate::{ErrorType, VariantName};
+
+type Hook = Arc<dyn Fn(&ErrorEvent) + Send + Sync>;
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+static HOOKS: RwLock<Vec<(HookId, Hook)>> = RwLock::new(Vec::new());
+
+thread_local! {
+    static RUNNING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Identifier of an installed hook, to remove it with [remove_hook].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct HookId(u64);
+
+/// A newly created error frame, as seen by hooks.
+pub struct ErrorEvent<'a> {
+    kind: &'a dyn Any,
+    debug: &'a dyn fmt::Debug,
+    location: &'static Location<'static>,
+    depth: usize,
+}
+
+impl<'a> ErrorEvent<'a> {
+    /// Get the error kind of the new frame, if it has the given type.
+    pub fn kind<K>(&self) -> Option<&'a K>
+    where
+        K: 'static,
+    {
+        self.kind.downcast_ref()
+    }
+
+    /// Get the variant name of the error kind of the new frame, such as
+    /// `Load` for `Load("a.txt")`, to use as a label for instance.
+    pub fn variant_name(&self) -> VariantName<'a, dyn fmt::Debug + 'a> {
+        VariantName(self.debug)
+    }
+
+    /// Get the location where the new frame was constructed.
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+    /// Get the depth of the new error, see [ErrorType::depth].
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+}
+
+impl<'a> fmt::Debug for ErrorEvent<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ErrorEvent")
+            .field("kind", &self.debug)
+            .field("location", &self.location)
+            .field("depth", &self.depth)
+            .finish()
+    }
+}
+
+/// Install a hook run whenever an error is created, in any thread.
+pub fn add_hook(hook: impl Fn(&ErrorEvent) + Send + Sync + 'static) -> HookId {
+    let id = HookId(NEXT_ID.fetch_add(1, Ordering::Relaxed));
+    let mut hooks = HOOKS.write().unwrap_or_else(|x| x.into_inner());
+    hooks.push((id, Arc::new(hook)));
+    INSTALLED.store(true, Ordering::Release);
+
+    id
+}
+
+/// Remove a hook installed by [add_hook]. Returns whether it was installed.
+pub fn remove_hook(id: HookId) -> bool {
+    let mut hooks = HOOKS.write().unwrap_or_else(|x| x.into_inner());
+    let len = hooks.len();
+    hooks.retain(|(x, _)| *x != id);
+    INSTALLED.store(!hooks.is_empty(), Ordering::Release);
+
+    hooks.len() != len
+}
+
+/// Run the installed hooks for a newly created error.
+///
+/// Every generated error type meets the bounds: its kind is a field of a
+/// struct deriving `Debug`, without lifetime parameters.
+#[doc(hidden)]
+#[inline]
+pub fn __run_hooks<E>(err: &E)
+where
+    E: ErrorType,
+    E::ErrorKind: fmt::Debug + 'static,
+{
+    if INSTALLED.load(Ordering::Acquire) {
+        run_hooks(err);
+    }
+}
+
+#[cold]
+fn run_hooks<E>(err: &E)
+where
+    E: ErrorType,
+    E::ErrorKind: fmt::Debug + 'static,
+{
+    if RUNNING.with(|x| x.replace(true)) {
+        return;
+    }
+    let _running = Running;
+
+    // Hooks are cloned out of the lock so that they can install other hooks.
+    let hooks: Vec<_> = HOOKS
+        .read()
+        .unwrap_or_else(|x| x.into_inner())
+        .iter()
+        .map(|(_, hook)| hook.clone())
+        .collect();
+    let event = ErrorEvent {
+        kind: err.kind(),
+        debug: err.kind(),
+        location: err.location(),
+        depth: err.depth(),
+    };
+    for hook in hooks {
+        hook(&event);
+    }
+}
+
+/// Marks the current thread as not running hooks anymore when dropped, even
+/// if a hook panics.
+struct Running;
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        RUNNING.with(|x| x.set(false));
+    }
+}
@@ -0,0 +1,261 @@
+//! Pieces shared by the conversions to and from foreign error libraries.
+
+use std::{error::Error as StdError, fmt, panic::Location, sync::Arc};
+
+use crate::ErrorType;
+
+/// A frame of an error chain kept in the chain of a foreign error, or a layer
+/// of the chain of a foreign error kept as the foreign source of a frame.
+///
+/// When an error is converted into an `anyhow::Error` or an `eyre::Report`,
+/// each of its frames becomes one of these, nested as the sources of one
+/// another. The kinds and locations show up in the chain of the foreign
+/// error, and the outermost frame can be retrieved with
+/// `downcast_ref::<ContextFrame>()`.
+///
+/// The other way around, when a frame is added on top of a foreign error,
+/// each layer of its chain becomes one of these. Layers which were already
+/// frames keep their location, the others only have a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextFrame {
+    message: String,
+    location: Option<&'static Location<'static>>,
+    source: Option<Box<ContextFrame>>,
+}
+
+impl ContextFrame {
+    /// Create a new [ContextFrame] from the message of an error kind and the
+    /// location of its frame.
+    pub fn new(message: impl Into<String>, location: &'static Location<'static>) -> Self {
+        Self {
+            message: message.into(),
+            location: Some(location),
+            source: None,
+        }
+    }
+
+    /// Create a new [ContextFrame] from a layer of the chain of a foreign
+    /// error.
+    fn from_layer(layer: &(dyn StdError + 'static)) -> Self {
+        match layer.downcast_ref::<Self>() {
+            Some(frame) => Self {
+                message: frame.message.clone(),
+                location: frame.location,
+                source: None,
+            },
+            None => Self {
+                message: layer.to_string(),
+                location: None,
+                source: None,
+            },
+        }
+    }
+
+    /// Get the display string of the error kind of this frame, or of the
+    /// layer of the foreign error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Get the location where this frame was constructed, if it was a frame
+    /// of an error chain.
+    pub fn location(&self) -> Option<&'static Location<'static>> {
+        self.location
+    }
+}
+
+impl fmt::Display for ContextFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(location) = self.location {
+            write!(f, " at {location}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl StdError for ContextFrame {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.source
+            .as_deref()
+            .map(|x| x as &(dyn StdError + 'static))
+    }
+}
+
+/// An error of a foreign error library, such as `anyhow::Error`.
+pub(crate) trait ForeignError: Sized {
+    /// Get the layers of the chain of this error, outermost first.
+    fn layers(&self) -> Vec<&(dyn StdError + 'static)>;
+
+    /// Get the context or the error at the root of the contexts of this error
+    /// which [ForeignError::downcast] would take, if it has the given type.
+    fn downcast_ref<E>(&self) -> Option<&E>
+    where
+        E: fmt::Display + fmt::Debug + Send + Sync + 'static;
+
+    /// Take the outermost context of this error with the given type, or else
+    /// the error at the root of the contexts, if it has the given type.
+    fn downcast<E>(self) -> Result<E, Self>
+    where
+        E: fmt::Display + fmt::Debug + Send + Sync + 'static;
+}
+
+/// Create an error of the given kind caused by a foreign error.
+///
+/// If the foreign error is made of contexts on top of an error of the same
+/// type, that error becomes the cause of the new frame. The layers of the
+/// chain above it, or all of them otherwise, become [ContextFrame]s, kept as
+/// the foreign source of the new frame. The layers are all kept as well when
+/// one of the contexts has the same type, as the error below it could not be
+/// taken out.
+pub(crate) fn from_foreign<E, F>(
+    kind: E::ErrorKind,
+    err: F,
+    location: &'static Location<'static>,
+) -> E
+where
+    E: ErrorType + StdError + Send + Sync + 'static,
+    F: ForeignError,
+{
+    let layers = err.layers();
+    // A context of the same type on top of another error is not the root of
+    // the chain, but it is the one the downcast would take, dropping the error
+    // below it: the root is only taken as the cause if it is the downcast one
+    let root = layers.iter().position(|x| x.is::<E>()).filter(|&root| {
+        match (layers[root].downcast_ref::<E>(), err.downcast_ref::<E>()) {
+            (Some(root), Some(downcast)) => std::ptr::eq(root, downcast),
+            _ => false,
+        }
+    });
+    let mut layers: Vec<_> = layers.into_iter().map(ContextFrame::from_layer).collect();
+    let cause = match root.map(|root| (root, err.downcast::<E>())) {
+        Some((root, Ok(cause))) => {
+            layers.truncate(root);
+            Some(Box::new(cause))
+        }
+        _ => None,
+    };
+
+    let err = E::new(kind, cause, location);
+    match nest(layers) {
+        Some(source) => {
+            let mut parts = err.into_parts();
+            parts.source = Some(Arc::new(source));
+            E::from_parts(parts)
+        }
+        None => err,
+    }
+}
+
+/// Convert an error chain into nested [ContextFrame]s, to be kept in a
+/// foreign error.
+///
+/// Every frame becomes a [ContextFrame], depth first, each followed by the
+/// foreign sources of the frame as plain messages.
+pub(crate) fn into_foreign<E>(err: &E) -> ContextFrame
+where
+    E: ErrorType,
+    E::ErrorKind: fmt::Display,
+{
+    let mut layers = Vec::new();
+    flatten(err, &mut layers);
+
+    nest(layers).expect("an error has at least one frame")
+}
+
+/// Add the frames of an error chain to `layers`, depth first, each followed
+/// by its foreign sources.
+fn flatten<E>(err: &E, layers: &mut Vec<ContextFrame>)
+where
+    E: ErrorType,
+    E::ErrorKind: fmt::Display,
+{
+    layers.push(ContextFrame::new(err.kind().to_string(), err.location()));
+    let first = err.foreign_source().map(|x| x as &(dyn StdError + 'static));
+    layers.extend(std::iter::successors(first, |&x| x.source()).map(ContextFrame::from_layer));
+    for cause in err.causes() {
+        flatten(cause, layers);
+    }
+}
+
+/// Nest layers, outermost first, as the sources of one another.
+fn nest(layers: Vec<ContextFrame>) -> Option<ContextFrame> {
+    layers.into_iter().rev().fold(None, |source, mut layer| {
+        layer.source = source.map(Box::new);
+        Some(layer)
+    })
+}
+
+/// Generate the items of the module of a foreign error library: the
+/// conversion of its errors into error chains and back.
+macro_rules! foreign_module {
+    (
+        $library:ident::$error:ident,
+        $from:ident,
+        $into_trait:ident::$into:ident,
+        $description:literal
+    ) => {
+        use std::{error::Error as StdError, fmt, panic::Location};
+
+        use crate::{
+            interop::{from_foreign, into_foreign, ForeignError},
+            ErrorType,
+        };
+
+        #[doc = concat!("Create an error of the given kind caused by an [", stringify!($library), "::", stringify!($error), "].")]
+        ///
+        #[doc = concat!("If the ", $description, " is made of contexts on top of an error of the same")]
+        /// type, that error becomes the cause of the new frame. The layers of the
+        /// chain above it, or all of them otherwise, become [ContextFrame]s, kept as
+        /// the foreign source of the new frame.
+        pub fn $from<E>(kind: E::ErrorKind, err: $error, location: &'static Location<'static>) -> E
+        where
+            E: ErrorType + StdError + Send + Sync + 'static,
+        {
+            from_foreign(kind, err, location)
+        }
+
+        impl ForeignError for $error {
+            fn layers(&self) -> Vec<&(dyn StdError + 'static)> {
+                $error::chain(self).collect()
+            }
+
+            fn downcast_ref<E>(&self) -> Option<&E>
+            where
+                E: fmt::Display + fmt::Debug + Send + Sync + 'static,
+            {
+                $error::downcast_ref(self)
+            }
+
+            fn downcast<E>(self) -> Result<E, Self>
+            where
+                E: fmt::Display + fmt::Debug + Send + Sync + 'static,
+            {
+                $error::downcast(self)
+            }
+        }
+
+        #[doc = concat!("Conversion of an error chain into an [", stringify!($library), "::", stringify!($error), "].")]
+        pub trait $into_trait {
+            #[doc = concat!("Convert this error into an [", stringify!($library), "::", stringify!($error), "].")]
+            ///
+            /// Every frame of the chain, depth first, becomes a [ContextFrame],
+            /// keeping the display string of its kind and its location. The foreign
+            /// sources of each frame are kept as plain messages right below it.
+            fn $into(self) -> $error;
+        }
+
+        impl<E> $into_trait for E
+        where
+            E: ErrorType,
+            E::ErrorKind: fmt::Display,
+        {
+            fn $into(self) -> $error {
+                $error::new(into_foreign(&self))
+            }
+        }
+    };
+}
+
+pub(crate) use foreign_module;
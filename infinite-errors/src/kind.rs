@@ -0,0 +1,78 @@
+//! Metadata attached to error kinds.
+
+use core::fmt::{self, Write};
+
+/// Severity of an error kind.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Only relevant when debugging.
+    Debug,
+    /// Expected failure, such as invalid user input.
+    Info,
+    /// Failure which may need attention.
+    Warning,
+    /// Failure which needs attention.
+    Error,
+    /// Failure which needs immediate attention.
+    Critical,
+}
+
+/// Per-variant metadata of an error kind.
+///
+/// Usually derived with [ErrorKind](crate::ErrorKind) and its
+/// `#[kind(code = "...", severity = ...)]` attribute.
+pub trait KindMetadata {
+    /// Get the stable code of this error kind, if one exists.
+    fn code(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Get the severity of this error kind.
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+}
+
+/// Displays the variant name of an error kind: `Load` for `Load("a.txt")`.
+///
+/// The variant name is the leading identifier of the [Debug](fmt::Debug)
+/// string of the kind, which derived implementations start with. It is what
+/// [Fingerprint](crate::Fingerprint) hashes.
+#[derive(Clone, Copy, Debug)]
+pub struct VariantName<'a, K: ?Sized>(pub &'a K);
+
+impl<K: fmt::Debug + ?Sized> fmt::Display for VariantName<'_, K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut writer = LeadingIdentifier {
+            inner: f,
+            ended: false,
+        };
+        match write!(writer, "{:?}", self.0) {
+            // Stopped formatting right after the variant name
+            Err(_) if writer.ended => Ok(()),
+            result => result,
+        }
+    }
+}
+
+/// Writer forwarding the leading identifier of what is written to it, and
+/// failing right after it to stop formatting.
+struct LeadingIdentifier<W> {
+    inner: W,
+    ended: bool,
+}
+
+impl<W: Write> Write for LeadingIdentifier<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = s
+            .find(|c: char| !c.is_alphanumeric() && c != '_')
+            .unwrap_or(s.len());
+        self.inner.write_str(&s[..end])?;
+        if end < s.len() {
+            self.ended = true;
+            return Err(fmt::Error);
+        }
+
+        Ok(())
+    }
+}
@@ -1,14 +1,130 @@
 //! Generic error handling framework with static backtraces.
+//!
+//! The `std` feature is enabled by default. Without it, this crate only
+//! depends on `core` and `alloc`: error types implement [core::error::Error]
+//! and the integrations with other crates, hooks and runtime backtraces are
+//! unavailable. Hooks, see the `hook` module, are behind the `hooks`
+//! feature, which enables `std` for the `RwLock` and `thread_local!` they
+//! rely on.
+//!
+//! The `derive-error` and `future` features are also enabled by default. The
+//! former re-exports the `Error` derive of `derive_more` and requires `std`.
+//! The latter adds context to futures and streams, see [future], and works
+//! without `std`.
 
-use std::panic::Location;
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+mod aggregate;
+#[cfg(feature = "anyhow")]
+pub mod anyhow;
+mod attachment;
+#[cfg(feature = "axum")]
+pub mod axum;
+mod backtrace;
+mod convert;
+#[cfg(feature = "eyre")]
+pub mod eyre;
+mod fingerprint;
+mod function;
+#[cfg(feature = "future")]
+pub mod future;
+#[cfg(feature = "hooks")]
+pub mod hook;
+#[cfg(any(feature = "anyhow", feature = "eyre"))]
+mod interop;
+mod kind;
+mod problem;
+mod report;
+#[cfg(feature = "serde")]
+pub mod serde;
+mod status;
+#[cfg(feature = "tonic")]
+pub mod tonic;
+mod trace;
+
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::panic::Location;
+#[cfg(feature = "std")]
+use std::backtrace::Backtrace;
+
+pub use aggregate::{CollectErrors, Errors};
+pub use attachment::{Attach, AttachmentValue, Attachments};
+pub use backtrace::OriginBacktrace;
+pub use convert::{KindMapping, Parts};
+#[cfg(feature = "derive-error")]
 pub use derive_more::Error;
-pub use infinite_errors_macros::err_context;
+pub use fingerprint::Fingerprint;
+pub use function::{FunctionContext, FunctionFrame};
+pub use infinite_errors_macros::{err_context, error_type, ErrorKind};
+pub use kind::{KindMetadata, Severity, VariantName};
+pub use problem::{FrameVisibility, ProblemDetails, ProblemFrame, ProblemPolicy};
+pub use report::Report;
+pub use status::{ErrorBody, ErrorStatus, GrpcCode};
+pub use trace::{OriginTrace, TraceContext};
+
+/// Items used by the code generated by the macros of this crate, which cannot
+/// name `alloc` or `std` themselves in `no_std` crates.
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::{boxed::Box, format, sync::Arc, vec, vec::Vec};
+    #[cfg(feature = "std")]
+    pub use std::backtrace::Backtrace;
+
+    #[cfg(feature = "hooks")]
+    pub use crate::hook::__run_hooks as run_hooks;
+
+    /// Get the depth of a new frame with the given causes, from theirs.
+    #[inline]
+    pub fn depth<E>(causes: &[E]) -> usize
+    where
+        E: crate::ErrorType,
+    {
+        1 + causes.iter().map(E::depth).max().unwrap_or(0)
+    }
+
+    /// Record the function frame of a function with `err_context` on its
+    /// error if `enabled`, the value of the `INFINITE_ERRORS_FUNCTION_CONTEXT`
+    /// environment variable when the crate of the function was compiled, is
+    /// set and not `0`.
+    #[inline]
+    pub fn function_context<T, E>(
+        result: Result<T, E>,
+        enabled: Option<&str>,
+        name: &'static str,
+        location: &'static core::panic::Location<'static>,
+    ) -> Result<T, E>
+    where
+        E: crate::ErrorType,
+    {
+        match enabled {
+            Some(enabled) if enabled != "0" => {
+                crate::FunctionContext::function_context(result, || {
+                    crate::FunctionFrame::new(name, location, Vec::new())
+                })
+            }
+            _ => result,
+        }
+    }
+
+    /// Hooks are only supported with the `hooks` feature.
+    #[cfg(not(feature = "hooks"))]
+    #[inline(always)]
+    pub fn run_hooks<E>(_err: &E) {}
+}
 
 /// Generate a rich error type using a given error kind.
 ///
 /// The type will be called `Error`. Also generates an `ErrorContext` trait
-/// similar to [ErrorContext] but specialized for this new error type.
+/// similar to [ErrorContext] but specialized for this new error type, a
+/// `SourceContext` trait to wrap foreign errors implementing
+/// [core::error::Error] while keeping their own chain of sources, an
+/// `OptionContext` trait to turn [None] into an error. With the `future`
+/// feature, the `FutureErrorContext` and `TryStreamErrorContext` traits add
+/// context to the errors of futures and streams. With the `anyhow` and `eyre`
+/// features, `ErrorContext` also adds context to `anyhow::Result` and
+/// `eyre::Result`.
 ///
 /// The reason why we cannot define an error type in this crate and export it
 /// is because orphan rules would make the `?` operator more awkward to use.
@@ -22,89 +138,423 @@ pub use infinite_errors_macros::err_context;
 /// declare_error_type!(ErrorKind);
 /// ```
 ///
-/// Both the error type and the generated `ErrorContext` trait will be `pub`.
+/// The error type and the generated traits will be `pub`. Use the [error_type]
+/// attribute for more control over the generated items.
+///
+/// # Display
+///
+/// The error is displayed on a single line with the kinds of all of its
+/// frames, followed by the message of the foreign source of the innermost
+/// frame, if any. As the chain is already part of every line, walking
+/// [core::error::Error::source] is not the way to print it: use the `{:#}`
+/// [Report] instead.
 #[macro_export]
 macro_rules! declare_error_type {
     ($error_kind:ident) => {
-        /// Generic eror type with backtrace.
-        #[derive(::std::fmt::Debug, ::infinite_errors::Error)]
-        pub struct Error {
-            kind: $error_kind,
-            cause: ::std::option::Option<::std::boxed::Box<Error>>,
-            location: &'static ::std::panic::Location<'static>,
+        $crate::__declare_error_type! {
+            /// Generic eror type with backtrace.
+            pub struct Error;
+            kind = $error_kind;
+            context = ErrorContext;
+            source_context = SourceContext;
+            option_context = OptionContext;
+            future_context = FutureErrorContext;
+            stream_context = TryStreamErrorContext;
         }
+    };
+}
 
-        impl Error {
-            /// Create a new [Error] from an error kind and an error [Location].
+/// Implementation of [declare_error_type] and [error_type].
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __declare_error_type {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident;
+        kind = $kind:ty;
+        context = $context:ident;
+        source_context = $source_context:ident;
+        option_context = $option_context:ident;
+        future_context = $future_context:ident;
+        stream_context = $stream_context:ident;
+        $(result = $result:ident;)?
+    ) => {
+        $(#[$attr])*
+        #[derive(::core::fmt::Debug)]
+        $vis struct $name {
+            kind: $kind,
+            causes: ::infinite_errors::__private::Vec<$name>,
+            depth: usize,
+            source: ::core::option::Option<
+                ::infinite_errors::__private::Arc<
+                    dyn ::core::error::Error + ::core::marker::Send + ::core::marker::Sync,
+                >,
+            >,
+            location: &'static ::core::panic::Location<'static>,
+            backtrace: ::infinite_errors::OriginBacktrace,
+            trace: ::infinite_errors::OriginTrace,
+            attachments: ::infinite_errors::Attachments,
+            functions: ::core::option::Option<
+                ::infinite_errors::__private::Box<::infinite_errors::__private::Vec<::infinite_errors::FunctionFrame>>,
+            >,
+        }
+
+        impl $name {
+            /// Create a new [Self] from an error kind and an error [Location].
             pub fn new(
-                kind: $error_kind,
-                location: &'static ::std::panic::Location<'static>,
+                kind: $kind,
+                location: &'static ::core::panic::Location<'static>,
             ) -> Self {
-                Self {
+                ::infinite_errors::ErrorType::with_causes(kind, ::infinite_errors::__private::Vec::new(), location)
+            }
+
+            /// Create a new [Self] from an error kind and a foreign error
+            /// which caused it.
+            ///
+            /// The foreign error is kept as is: it can be retrieved with
+            /// [Self::foreign_source] and its own chain of sources is still
+            /// reachable through [core::error::Error::source].
+            #[track_caller]
+            pub fn with_source(
+                kind: $kind,
+                source: impl ::core::convert::Into<
+                    ::infinite_errors::__private::Box<
+                        dyn ::core::error::Error + ::core::marker::Send + ::core::marker::Sync,
+                    >,
+                >,
+            ) -> Self {
+                let source: ::infinite_errors::__private::Box<
+                    dyn ::core::error::Error + ::core::marker::Send + ::core::marker::Sync,
+                > = source.into();
+                let err = Self {
                     kind,
-                    cause: ::std::option::Option::None,
-                    location,
-                }
+                    causes: ::infinite_errors::__private::Vec::new(),
+                    depth: 1,
+                    source: ::core::option::Option::Some(source.into()),
+                    location: ::core::panic::Location::caller(),
+                    backtrace: ::infinite_errors::OriginBacktrace::capture(),
+                    trace: ::infinite_errors::OriginTrace::capture(),
+                    attachments: ::infinite_errors::Attachments::default(),
+                    functions: ::core::option::Option::None,
+                };
+                ::infinite_errors::__private::run_hooks(&err);
+
+                err
+            }
+
+            /// Create a new [Self] from an error kind and all of its causes.
+            ///
+            /// Useful when an operation fails for several reasons at once,
+            /// such as a validation pass or a set of parallel calls.
+            #[track_caller]
+            pub fn aggregate(
+                kind: $kind,
+                causes: impl ::core::iter::IntoIterator<Item = $name>,
+            ) -> Self {
+                ::infinite_errors::ErrorType::with_causes(
+                    kind,
+                    causes.into_iter().collect(),
+                    ::core::panic::Location::caller(),
+                )
             }
 
             /// Get the internal error kind.
-            pub fn kind(&self) -> &$error_kind {
+            pub fn kind(&self) -> &$kind {
                 &self.kind
             }
 
-            /// Get the cause for this error, if one exists.
-            pub fn cause(&self) -> ::std::option::Option<&Self> {
-                self.cause.as_deref()
+            /// Get the cause for this error, if one exists. If this error has
+            /// more than one cause, the first one is returned.
+            pub fn cause(&self) -> ::core::option::Option<&Self> {
+                self.causes.first()
+            }
+
+            /// Get all the causes for this error.
+            pub fn causes(&self) -> &[Self] {
+                &self.causes
+            }
+
+            /// Get the foreign error which caused this error, if one exists.
+            pub fn foreign_source(
+                &self,
+            ) -> ::core::option::Option<
+                &(dyn ::core::error::Error + ::core::marker::Send + ::core::marker::Sync + 'static),
+            > {
+                self.source.as_deref()
             }
 
-            /// Get the location where this [Error] was constructed.
-            pub fn location(&self) -> &'static ::std::panic::Location<'static> {
+            /// Get the foreign error which caused this error if it is of type
+            /// `E`.
+            pub fn downcast_source<E>(&self) -> ::core::option::Option<&E>
+            where
+                E: ::core::error::Error + 'static,
+            {
+                self.source.as_deref()?.downcast_ref()
+            }
+
+            /// Get the location where this [Self] was constructed.
+            pub fn location(&self) -> &'static ::core::panic::Location<'static> {
                 self.location
             }
+
+            /// Get the key-value attachments of this frame.
+            pub fn attachments(&self) -> &::infinite_errors::Attachments {
+                &self.attachments
+            }
+
+            /// Attach a value with the given key to this frame.
+            pub fn attach(
+                mut self,
+                key: &'static str,
+                value: impl ::infinite_errors::AttachmentValue,
+            ) -> Self {
+                self.attachments.insert(key, value);
+                self
+            }
+
+            /// Get the functions this frame went through, recorded by
+            /// `#[err_context(function)]`, innermost first.
+            pub fn functions(&self) -> &[::infinite_errors::FunctionFrame] {
+                ::infinite_errors::ErrorType::functions(self)
+            }
+
+            $crate::__backtrace!(inherent);
+
+            /// Get the trace and span ids of the `minitrace` span active where
+            /// the outermost frame of this error that captured them was
+            /// created, if one exists.
+            ///
+            /// Trace contexts are only captured when the `minitrace` feature of
+            /// `infinite-errors` is enabled and a local parent span is set.
+            pub fn trace_context(&self) -> ::core::option::Option<&::infinite_errors::TraceContext> {
+                ::infinite_errors::ErrorType::trace_context(self)
+            }
+
+            /// Iterate over this error and all of its causes, depth-first,
+            /// starting with this error.
+            pub fn chain(&self) -> ::infinite_errors::Chain<'_, Self> {
+                ::infinite_errors::ErrorType::chain(self)
+            }
+
+            /// Get the innermost cause of this error, following the first
+            /// cause of each frame, or the error itself if it has no cause.
+            pub fn root_cause(&self) -> &Self {
+                ::infinite_errors::ErrorType::root_cause(self)
+            }
+
+            /// Get the number of frames in the longest path from this error to
+            /// one of its innermost causes.
+            pub fn depth(&self) -> usize {
+                ::infinite_errors::ErrorType::depth(self)
+            }
+
+            /// Render this error as a multi-line [Report](::infinite_errors::Report),
+            /// with one line per frame.
+            pub fn report(&self) -> ::infinite_errors::Report<'_, Self> {
+                ::infinite_errors::Report::new(self)
+            }
+
+            /// Get the outermost frame in the chain of this error whose kind
+            /// matches the given predicate.
+            pub fn find_kind(
+                &self,
+                predicate: impl FnMut(&$kind) -> bool,
+            ) -> ::core::option::Option<&Self> {
+                ::infinite_errors::ErrorType::find_kind(self, predicate)
+            }
+
+            /// Get the fingerprint of the failure path of this error, to
+            /// group identical failures in logs and metrics.
+            pub fn fingerprint(&self) -> ::infinite_errors::Fingerprint {
+                ::infinite_errors::ErrorType::fingerprint(self)
+            }
         }
 
-        impl ::infinite_errors::ErrorType for Error {
-            type ErrorKind = $error_kind;
+        impl ::infinite_errors::ErrorType for $name {
+            type ErrorKind = $kind;
 
-            fn new(
+            fn with_causes(
                 kind: Self::ErrorKind,
-                cause: ::std::option::Option<::std::boxed::Box<Self>>,
-                location: &'static ::std::panic::Location<'static>,
+                causes: ::infinite_errors::__private::Vec<Self>,
+                location: &'static ::core::panic::Location<'static>,
             ) -> Self {
-                Self {
+                let backtrace = if causes.is_empty() {
+                    ::infinite_errors::OriginBacktrace::capture()
+                } else {
+                    ::infinite_errors::OriginBacktrace::default()
+                };
+
+                let err = Self {
                     kind,
-                    cause,
+                    depth: ::infinite_errors::__private::depth(&causes),
+                    causes,
+                    source: ::core::option::Option::None,
                     location,
+                    backtrace,
+                    trace: ::infinite_errors::OriginTrace::capture(),
+                    attachments: ::infinite_errors::Attachments::default(),
+                    functions: ::core::option::Option::None,
+                };
+                ::infinite_errors::__private::run_hooks(&err);
+
+                err
+            }
+
+            fn with_foreign_source(
+                kind: Self::ErrorKind,
+                source: ::infinite_errors::__private::Box<
+                    dyn ::core::error::Error + ::core::marker::Send + ::core::marker::Sync,
+                >,
+                location: &'static ::core::panic::Location<'static>,
+            ) -> Self {
+                let err = Self {
+                    kind,
+                    causes: ::infinite_errors::__private::Vec::new(),
+                    depth: 1,
+                    source: ::core::option::Option::Some(source.into()),
+                    location,
+                    backtrace: ::infinite_errors::OriginBacktrace::capture(),
+                    trace: ::infinite_errors::OriginTrace::capture(),
+                    attachments: ::infinite_errors::Attachments::default(),
+                    functions: ::core::option::Option::None,
+                };
+                ::infinite_errors::__private::run_hooks(&err);
+
+                err
+            }
+
+            fn into_parts(self) -> ::infinite_errors::Parts<Self> {
+                ::infinite_errors::Parts {
+                    kind: self.kind,
+                    causes: self.causes,
+                    source: self.source,
+                    location: self.location,
+                    backtrace: self.backtrace,
+                    trace: self.trace,
+                    attachments: self.attachments,
+                    functions: self.functions.map_or_else(::infinite_errors::__private::Vec::new, |x| *x),
                 }
             }
+
+            fn from_parts(parts: ::infinite_errors::Parts<Self>) -> Self {
+                Self {
+                    kind: parts.kind,
+                    depth: ::infinite_errors::__private::depth(&parts.causes),
+                    causes: parts.causes,
+                    source: parts.source,
+                    location: parts.location,
+                    backtrace: parts.backtrace,
+                    trace: parts.trace,
+                    attachments: parts.attachments,
+                    functions: (!parts.functions.is_empty())
+                        .then(|| ::infinite_errors::__private::Box::new(parts.functions)),
+                }
+            }
+
+            fn kind(&self) -> &Self::ErrorKind {
+                &self.kind
+            }
+
+            fn causes(&self) -> &[Self] {
+                &self.causes
+            }
+
+            fn depth(&self) -> usize {
+                self.depth
+            }
+
+            fn foreign_source(
+                &self,
+            ) -> ::core::option::Option<
+                &(dyn ::core::error::Error + ::core::marker::Send + ::core::marker::Sync + 'static),
+            > {
+                self.source.as_deref()
+            }
+
+            fn location(&self) -> &'static ::core::panic::Location<'static> {
+                self.location
+            }
+
+            $crate::__backtrace!(frame);
+
+            fn frame_trace_context(
+                &self,
+            ) -> ::core::option::Option<&::infinite_errors::TraceContext> {
+                self.trace.get()
+            }
+
+            fn attachments(&self) -> &::infinite_errors::Attachments {
+                &self.attachments
+            }
+
+            fn attachments_mut(&mut self) -> &mut ::infinite_errors::Attachments {
+                &mut self.attachments
+            }
+
+            fn functions(&self) -> &[::infinite_errors::FunctionFrame] {
+                self.functions
+                    .as_deref()
+                    .map_or(&[], ::infinite_errors::__private::Vec::as_slice)
+            }
+
+            fn functions_mut(
+                &mut self,
+            ) -> &mut ::infinite_errors::__private::Vec<::infinite_errors::FunctionFrame> {
+                self.functions.get_or_insert_with(::core::default::Default::default)
+            }
         }
 
-        impl ::std::fmt::Display for Error {
-            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
-                write!(f, "{}", self.kind)?;
-                if let Some(cause) = &self.cause {
-                    write!(f, ": {cause}")?;
+        impl ::core::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                if f.alternate() {
+                    return write!(f, "{}", ::infinite_errors::Report::new(self));
+                }
+
+                ::infinite_errors::__display_chain(self, f)?;
+                if let ::core::option::Option::Some(trace) =
+                    ::infinite_errors::ErrorType::trace_context(self)
+                {
+                    write!(f, " [{trace}]")?;
                 }
 
                 Ok(())
             }
         }
 
-        impl<T> ::std::convert::From<T> for Error
+        impl ::core::error::Error for $name {
+            /// Get the first cause of this error, or its foreign source.
+            ///
+            /// [Display](::core::fmt::Display) already renders the whole
+            /// chain, so this is meant to inspect and downcast the chain, not
+            /// to print it: reporters walking `source()`, such as `anyhow` and
+            /// `eyre`, repeat the frames below each line. The `{:#}`
+            /// [Report](::infinite_errors::Report) shows each frame once.
+            fn source(&self) -> ::core::option::Option<&(dyn ::core::error::Error + 'static)> {
+                match self.causes.first() {
+                    ::core::option::Option::Some(cause) => ::core::option::Option::Some(cause),
+                    ::core::option::Option::None => self
+                        .source
+                        .as_deref()
+                        .map(|x| x as &(dyn ::core::error::Error + 'static)),
+                }
+            }
+        }
+
+        impl<T> ::core::convert::From<T> for $name
         where
-            T: ::std::convert::Into<$error_kind>,
+            T: ::core::convert::Into<$kind>,
         {
             #[track_caller]
             fn from(kind: T) -> Self {
-                Self::new(kind.into(), ::std::panic::Location::caller())
+                Self::new(kind.into(), ::core::panic::Location::caller())
             }
         }
 
         /// Helper trait to add context to errors.
-        pub trait ErrorContext<T> {
+        $vis trait $context<T> {
             /// Add an error kind to the top of the error backtrace.
             #[track_caller]
-            fn err_context(self, kind: $error_kind) -> ::std::result::Result<T, Error>;
+            fn err_context(self, kind: $kind) -> ::core::result::Result<T, $name>;
 
             /// Add an error kind returned by a function to the top of the error
             /// backtrace. The function should only be called if `self` is indeed an
@@ -112,38 +562,503 @@ macro_rules! declare_error_type {
             #[track_caller]
             fn err_context_with(
                 self,
-                kind: impl FnOnce() -> $error_kind,
-            ) -> ::std::result::Result<T, Error>;
+                kind: impl FnOnce() -> $kind,
+            ) -> ::core::result::Result<T, $name>;
         }
 
-        impl<T, OE> ErrorContext<T> for ::std::result::Result<T, OE>
+        impl<T, OE> $context<T> for ::core::result::Result<T, OE>
         where
-            OE: Into<Error>,
+            OE: Into<$name>,
         {
-            fn err_context(self, kind: $error_kind) -> ::std::result::Result<T, Error> {
-                self.map_err(|x| Error {
-                    kind,
-                    cause: ::std::option::Option::Some(::std::boxed::Box::new(x.into())),
-                    location: ::std::panic::Location::caller(),
+            fn err_context(self, kind: $kind) -> ::core::result::Result<T, $name> {
+                match self {
+                    ::core::result::Result::Ok(x) => ::core::result::Result::Ok(x),
+                    ::core::result::Result::Err(x) => {
+                        ::core::result::Result::Err(::infinite_errors::ErrorType::with_causes(
+                            kind,
+                            ::infinite_errors::__private::vec![x.into()],
+                            ::core::panic::Location::caller(),
+                        ))
+                    }
+                }
+            }
+
+            fn err_context_with(
+                self,
+                f: impl FnOnce() -> $kind,
+            ) -> ::core::result::Result<T, $name> {
+                match self {
+                    ::core::result::Result::Ok(x) => ::core::result::Result::Ok(x),
+                    ::core::result::Result::Err(x) => {
+                        ::core::result::Result::Err(::infinite_errors::ErrorType::with_causes(
+                            f(),
+                            ::infinite_errors::__private::vec![x.into()],
+                            ::core::panic::Location::caller(),
+                        ))
+                    }
+                }
+            }
+        }
+
+        impl<T> $context<T> for ::core::result::Result<T, ::infinite_errors::Errors<$name>> {
+            fn err_context(self, kind: $kind) -> ::core::result::Result<T, $name> {
+                let location = ::core::panic::Location::caller();
+                self.map_err(|x| {
+                    ::infinite_errors::ErrorType::with_causes(kind, x.into_inner(), location)
                 })
             }
 
             fn err_context_with(
                 self,
-                f: impl FnOnce() -> $error_kind,
-            ) -> ::std::result::Result<T, Error> {
-                self.map_err(|x| Error {
-                    kind: f(),
-                    cause: ::std::option::Option::Some(::std::boxed::Box::new(x.into())),
-                    location: ::std::panic::Location::caller(),
+                f: impl FnOnce() -> $kind,
+            ) -> ::core::result::Result<T, $name> {
+                let location = ::core::panic::Location::caller();
+                self.map_err(|x| {
+                    ::infinite_errors::ErrorType::with_causes(f(), x.into_inner(), location)
                 })
             }
         }
+
+        /// Helper trait to add context to foreign errors, keeping them as the
+        /// source of the new error.
+        $vis trait $source_context<T> {
+            /// Wrap the foreign error in a new error of the given kind.
+            #[track_caller]
+            fn source_context(self, kind: $kind) -> ::core::result::Result<T, $name>;
+
+            /// Wrap the foreign error in a new error of the kind returned by a
+            /// function. The function should only be called if `self` is
+            /// indeed an error.
+            #[track_caller]
+            fn source_context_with(
+                self,
+                kind: impl FnOnce() -> $kind,
+            ) -> ::core::result::Result<T, $name>;
+        }
+
+        impl<T, OE> $source_context<T> for ::core::result::Result<T, OE>
+        where
+            OE: ::core::convert::Into<
+                ::infinite_errors::__private::Box<
+                    dyn ::core::error::Error + ::core::marker::Send + ::core::marker::Sync,
+                >,
+            >,
+        {
+            fn source_context(self, kind: $kind) -> ::core::result::Result<T, $name> {
+                match self {
+                    ::core::result::Result::Ok(x) => ::core::result::Result::Ok(x),
+                    ::core::result::Result::Err(x) => {
+                        ::core::result::Result::Err($name::with_source(kind, x))
+                    }
+                }
+            }
+
+            fn source_context_with(
+                self,
+                f: impl FnOnce() -> $kind,
+            ) -> ::core::result::Result<T, $name> {
+                match self {
+                    ::core::result::Result::Ok(x) => ::core::result::Result::Ok(x),
+                    ::core::result::Result::Err(x) => {
+                        ::core::result::Result::Err($name::with_source(f(), x))
+                    }
+                }
+            }
+        }
+
+        /// Helper trait to turn [None] into an error.
+        $vis trait $option_context<T> {
+            /// Turn [None] into a new error of the given kind.
+            #[track_caller]
+            fn ok_or_context(self, kind: $kind) -> ::core::result::Result<T, $name>;
+
+            /// Turn [None] into a new error of the kind returned by a
+            /// function. The function should only be called if `self` is
+            /// indeed [None].
+            #[track_caller]
+            fn ok_or_context_with(
+                self,
+                kind: impl FnOnce() -> $kind,
+            ) -> ::core::result::Result<T, $name>;
+        }
+
+        impl<T> $option_context<T> for ::core::option::Option<T> {
+            fn ok_or_context(self, kind: $kind) -> ::core::result::Result<T, $name> {
+                match self {
+                    ::core::option::Option::Some(x) => ::core::result::Result::Ok(x),
+                    ::core::option::Option::None => ::core::result::Result::Err($name::from(kind)),
+                }
+            }
+
+            fn ok_or_context_with(
+                self,
+                f: impl FnOnce() -> $kind,
+            ) -> ::core::result::Result<T, $name> {
+                match self {
+                    ::core::option::Option::Some(x) => ::core::result::Result::Ok(x),
+                    ::core::option::Option::None => {
+                        ::core::result::Result::Err($name::from(f()))
+                    }
+                }
+            }
+        }
+
+        $crate::__future_context!($vis $name, $kind, $future_context, $stream_context);
+        $crate::__anyhow_context!($name, $kind, $context);
+        $crate::__eyre_context!($name, $kind, $context);
+        $crate::__axum_response!($name);
+        $crate::__tonic_status!($name);
+        $crate::__serialize!($name);
+
+        $(
+            /// Result type using this error type.
+            $vis type $result<T, E = $name> = ::core::result::Result<T, E>;
+        )?
+    };
+}
+
+/// Generate the backtrace accessors of an error type when the `std` feature
+/// is enabled, either the inherent `backtrace` method or
+/// [ErrorType::frame_backtrace].
+#[cfg(feature = "std")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __backtrace {
+    (inherent) => {
+        /// Get the runtime backtrace captured where the innermost frame of
+        /// this error was created, if one exists.
+        ///
+        /// Backtraces are only captured when the `backtrace` feature of
+        /// `infinite-errors` is enabled and `RUST_BACKTRACE` or
+        /// `RUST_LIB_BACKTRACE` allow it.
+        pub fn backtrace(
+            &self,
+        ) -> ::core::option::Option<&::infinite_errors::__private::Backtrace> {
+            ::infinite_errors::ErrorType::backtrace(self)
+        }
+    };
+    (frame) => {
+        fn frame_backtrace(
+            &self,
+        ) -> ::core::option::Option<&::infinite_errors::__private::Backtrace> {
+            self.backtrace.get()
+        }
+    };
+}
+
+#[cfg(not(feature = "std"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __backtrace {
+    ($($tt:tt)*) => {};
+}
+
+/// Generate the `FutureErrorContext` and `TryStreamErrorContext` traits of an
+/// error type when the `future` feature is enabled.
+#[cfg(feature = "future")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __future_context {
+    ($vis:vis $name:ident, $kind:ty, $future_context:ident, $stream_context:ident) => {
+        /// Helper trait to add context to the error output by a future, which
+        /// converts into this error type.
+        $vis trait $future_context<T>: ::core::marker::Sized {
+            /// Add a new error of the given kind when this future outputs an
+            /// error.
+            #[track_caller]
+            fn err_context(self, kind: $kind) -> ::infinite_errors::future::ErrContext<Self, $kind, $name> {
+                ::infinite_errors::future::ErrContext::new(
+                    self,
+                    kind,
+                    ::core::panic::Location::caller(),
+                )
+            }
+
+            /// Add a new error of the kind returned by a function when this
+            /// future outputs an error. The function is only called if there
+            /// is an error.
+            #[track_caller]
+            fn err_context_with<F>(
+                self,
+                kind: F,
+            ) -> ::infinite_errors::future::ErrContextWith<Self, F, $name>
+            where
+                F: ::core::ops::FnOnce() -> $kind,
+            {
+                ::infinite_errors::future::ErrContextWith::new(
+                    self,
+                    kind,
+                    ::core::panic::Location::caller(),
+                )
+            }
+        }
+
+        impl<T, OE, Fut> $future_context<T> for Fut
+        where
+            Fut: ::core::future::Future<Output = ::core::result::Result<T, OE>>,
+            OE: ::core::convert::Into<$name>,
+        {
+        }
+
+        /// Helper trait to add context to every error yielded by a stream,
+        /// which converts into this error type.
+        $vis trait $stream_context<T>: ::core::marker::Sized {
+            /// Add a new error of the given kind to every error yielded by
+            /// this stream. The kind must implement [Clone] to be used.
+            #[track_caller]
+            fn err_context(
+                self,
+                kind: $kind,
+            ) -> ::infinite_errors::future::StreamErrContext<Self, $kind, $name> {
+                ::infinite_errors::future::StreamErrContext::new(
+                    self,
+                    kind,
+                    ::core::panic::Location::caller(),
+                )
+            }
+
+            /// Add a new error of the kind returned by a function to every
+            /// error yielded by this stream. The function is only called for
+            /// errors.
+            #[track_caller]
+            fn err_context_with<F>(
+                self,
+                kind: F,
+            ) -> ::infinite_errors::future::StreamErrContextWith<Self, F, $name>
+            where
+                F: ::core::ops::FnMut() -> $kind,
+            {
+                ::infinite_errors::future::StreamErrContextWith::new(
+                    self,
+                    kind,
+                    ::core::panic::Location::caller(),
+                )
+            }
+        }
+
+        impl<T, St> $stream_context<T> for St
+        where
+            St: ::infinite_errors::future::TryStream<Ok = T>,
+            St::Error: ::core::convert::Into<$name>,
+        {
+        }
+    };
+}
+
+#[cfg(not(feature = "future"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __future_context {
+    ($($tt:tt)*) => {};
+}
+
+/// Implement the `ErrorContext` trait of an error type for the results of a
+/// foreign error library, shared by `__anyhow_context` and `__eyre_context`.
+///
+/// The blanket implementation over `OE: Into<$name>` does not cover the
+/// foreign error, so this one does not overlap with it, as long as the kind
+/// does not convert from the foreign error.
+#[cfg(any(feature = "anyhow", feature = "eyre"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __foreign_context {
+    ($name:ident, $kind:ty, $context:ident, $library:ident::$error:ident, $from:ident) => {
+        impl<T> $context<T> for ::core::result::Result<T, ::infinite_errors::$library::$error> {
+            fn err_context(self, kind: $kind) -> ::core::result::Result<T, $name> {
+                match self {
+                    ::core::result::Result::Ok(x) => ::core::result::Result::Ok(x),
+                    ::core::result::Result::Err(x) => {
+                        ::core::result::Result::Err(::infinite_errors::$library::$from(
+                            kind,
+                            x,
+                            ::core::panic::Location::caller(),
+                        ))
+                    }
+                }
+            }
+
+            fn err_context_with(
+                self,
+                f: impl FnOnce() -> $kind,
+            ) -> ::core::result::Result<T, $name> {
+                match self {
+                    ::core::result::Result::Ok(x) => ::core::result::Result::Ok(x),
+                    ::core::result::Result::Err(x) => {
+                        ::core::result::Result::Err(::infinite_errors::$library::$from(
+                            f(),
+                            x,
+                            ::core::panic::Location::caller(),
+                        ))
+                    }
+                }
+            }
+        }
+    };
+}
+
+/// Implement the `ErrorContext` trait of an error type for `anyhow::Result`
+/// when the `anyhow` feature is enabled.
+#[cfg(feature = "anyhow")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __anyhow_context {
+    ($name:ident, $kind:ty, $context:ident) => {
+        $crate::__foreign_context!($name, $kind, $context, anyhow::Error, from_anyhow);
+    };
+}
+
+#[cfg(not(feature = "anyhow"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __anyhow_context {
+    ($($tt:tt)*) => {};
+}
+
+/// Implement the `ErrorContext` trait of an error type for `eyre::Result`
+/// when the `eyre` feature is enabled.
+#[cfg(feature = "eyre")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __eyre_context {
+    ($name:ident, $kind:ty, $context:ident) => {
+        $crate::__foreign_context!($name, $kind, $context, eyre::Report, from_eyre);
+    };
+}
+
+#[cfg(not(feature = "eyre"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __eyre_context {
+    ($($tt:tt)*) => {};
+}
+
+/// Implement `IntoResponse` for an error type when the `axum` feature is
+/// enabled.
+///
+/// The kind is bound through [ErrorType] so that the implementation only
+/// exists for kinds implementing [ErrorStatus], instead of failing to compile
+/// for the others.
+#[cfg(feature = "axum")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __axum_response {
+    ($name:ident) => {
+        impl<K> ::infinite_errors::axum::IntoResponse for $name
+        where
+            $name: ::infinite_errors::ErrorType<ErrorKind = K>,
+            K: ::infinite_errors::ErrorStatus + ::core::fmt::Display,
+        {
+            fn into_response(self) -> ::infinite_errors::axum::Response {
+                ::infinite_errors::axum::into_response(&self)
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "axum"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __axum_response {
+    ($($tt:tt)*) => {};
+}
+
+/// Implement `From<Error> for tonic::Status` for an error type when the
+/// `tonic` feature is enabled. See `__axum_response` for the bounds.
+#[cfg(feature = "tonic")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __tonic_status {
+    ($name:ident) => {
+        impl<K> ::core::convert::From<$name> for ::infinite_errors::tonic::Status
+        where
+            $name: ::infinite_errors::ErrorType<ErrorKind = K>,
+            K: ::infinite_errors::ErrorStatus + ::core::fmt::Display,
+        {
+            fn from(err: $name) -> Self {
+                ::infinite_errors::tonic::into_status(&err)
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "tonic"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __tonic_status {
+    ($($tt:tt)*) => {};
+}
+
+/// Implement `Serialize` for an error type when the `serde` feature is
+/// enabled. See `__axum_response` for the bounds.
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __serialize {
+    ($name:ident) => {
+        impl<K> ::infinite_errors::serde::Serialize for $name
+        where
+            $name: ::infinite_errors::ErrorType<ErrorKind = K>,
+            K: ::infinite_errors::serde::Serialize + ::core::fmt::Display,
+        {
+            fn serialize<S>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error>
+            where
+                S: ::infinite_errors::serde::Serializer,
+            {
+                ::infinite_errors::serde::serialize(self, serializer)
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __serialize {
+    ($($tt:tt)*) => {};
+}
+
+/// Return early with an error of the given kind.
+///
+/// The error is created through [From], so it records the location where this
+/// macro is called. The enclosing function must return a [Result] whose error
+/// type can be created from the given kind.
+///
+/// ```ignore
+/// fn check(value: u32) -> Result<(), Error> {
+///     if value == 0 {
+///         bail!(ErrorKind::Zero);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[macro_export]
+macro_rules! bail {
+    ($kind:expr $(,)?) => {
+        return ::core::result::Result::Err(::core::convert::From::from($kind))
+    };
+}
+
+/// Return early with an error of the given kind if a condition is not met.
+///
+/// Like [bail], the error records the location where this macro is called.
+///
+/// ```ignore
+/// fn check(value: u32) -> Result<(), Error> {
+///     ensure!(value != 0, ErrorKind::Zero);
+///     Ok(())
+/// }
+/// ```
+#[macro_export]
+macro_rules! ensure {
+    ($cond:expr, $kind:expr $(,)?) => {
+        if !$cond {
+            $crate::bail!($kind);
+        }
     };
 }
 
 /// Trait for error types created by [declare_error_type].
-pub trait ErrorType {
+pub trait ErrorType: Sized {
     /// The `ErrorKind` type.
     type ErrorKind;
 
@@ -153,7 +1068,228 @@ pub trait ErrorType {
         kind: Self::ErrorKind,
         cause: Option<Box<Self>>,
         location: &'static Location<'static>,
+    ) -> Self {
+        Self::with_causes(kind, cause.map(|x| vec![*x]).unwrap_or_default(), location)
+    }
+
+    /// Create a new [ErrorType] with the given inner kind, list of causes and
+    /// error location.
+    fn with_causes(
+        kind: Self::ErrorKind,
+        causes: Vec<Self>,
+        location: &'static Location<'static>,
     ) -> Self;
+
+    /// Create a new [ErrorType] with the given inner kind, foreign error
+    /// which caused it and error location.
+    fn with_foreign_source(
+        kind: Self::ErrorKind,
+        source: Box<dyn core::error::Error + Send + Sync>,
+        location: &'static Location<'static>,
+    ) -> Self;
+
+    /// Split this error into the data of its outermost frame.
+    fn into_parts(self) -> Parts<Self>;
+
+    /// Create a new [ErrorType] from the data of its outermost frame.
+    fn from_parts(parts: Parts<Self>) -> Self;
+
+    /// Get the internal error kind.
+    fn kind(&self) -> &Self::ErrorKind;
+
+    /// Get all the causes for this error.
+    fn causes(&self) -> &[Self];
+
+    /// Get the cause for this error, if one exists. If this error has more
+    /// than one cause, the first one is returned.
+    fn cause(&self) -> Option<&Self> {
+        self.causes().first()
+    }
+
+    /// Get the foreign error which caused this error, if one exists.
+    fn foreign_source(&self) -> Option<&(dyn core::error::Error + Send + Sync + 'static)> {
+        None
+    }
+
+    /// Get the location where this error was constructed.
+    fn location(&self) -> &'static Location<'static>;
+
+    /// Get the key-value attachments of this frame.
+    fn attachments(&self) -> &Attachments;
+
+    /// Get the key-value attachments of this frame, to attach more values.
+    fn attachments_mut(&mut self) -> &mut Attachments;
+
+    /// Get the functions this frame went through, recorded by
+    /// `#[err_context(function)]`, innermost first.
+    fn functions(&self) -> &[FunctionFrame];
+
+    /// Get the functions this frame went through, to record more.
+    fn functions_mut(&mut self) -> &mut Vec<FunctionFrame>;
+
+    /// Get the runtime backtrace captured when this frame was created, if one
+    /// exists. Only frames without causes capture a backtrace.
+    #[cfg(feature = "std")]
+    fn frame_backtrace(&self) -> Option<&Backtrace> {
+        None
+    }
+
+    /// Get the runtime backtrace captured where the innermost frame of this
+    /// error was created, if one exists.
+    #[cfg(feature = "std")]
+    fn backtrace(&self) -> Option<&Backtrace> {
+        self.chain().find_map(Self::frame_backtrace)
+    }
+
+    /// Get the trace context captured when this frame was created, if one
+    /// exists. See [TraceContext].
+    fn frame_trace_context(&self) -> Option<&TraceContext> {
+        None
+    }
+
+    /// Get the trace context of the outermost frame of this error which
+    /// captured one, if one exists.
+    fn trace_context(&self) -> Option<&TraceContext> {
+        self.chain().find_map(Self::frame_trace_context)
+    }
+
+    /// Iterate over this error and all of its causes, depth-first, starting
+    /// with this error. Each item is a frame of the error backtrace, exposing
+    /// its kind and [Location].
+    fn chain(&self) -> Chain<'_, Self> {
+        Chain { stack: vec![self] }
+    }
+
+    /// Get the innermost cause of this error, following the first cause of
+    /// each frame, or the error itself if it has no cause.
+    fn root_cause(&self) -> &Self {
+        let mut current = self;
+        while let Some(cause) = current.cause() {
+            current = cause;
+        }
+
+        current
+    }
+
+    /// Get the number of frames in the longest path from this error to one of
+    /// its innermost causes. An error without a cause has depth 1.
+    fn depth(&self) -> usize {
+        __private::depth(self.causes())
+    }
+
+    /// Get the outermost frame in the chain of this error whose kind matches
+    /// the given predicate.
+    fn find_kind(&self, mut predicate: impl FnMut(&Self::ErrorKind) -> bool) -> Option<&Self> {
+        self.chain().find(|frame| predicate(frame.kind()))
+    }
+
+    /// Get the fingerprint of the failure path of this error, to group
+    /// identical failures in logs and metrics. See [Fingerprint].
+    fn fingerprint(&self) -> Fingerprint
+    where
+        Self::ErrorKind: core::fmt::Debug,
+    {
+        Fingerprint::new(self)
+    }
+
+    /// Convert this error into another error type, translating the kind of
+    /// every frame with the given function. Locations, foreign sources,
+    /// attachments and functions are kept as is. Kinds are translated from
+    /// the outermost frame to the innermost, depth-first.
+    ///
+    /// Handy to convert the error of another crate at the crate boundary:
+    ///
+    /// ```ignore
+    /// impl From<other::Error> for Error {
+    ///     fn from(err: other::Error) -> Self {
+    ///         err.map_kinds(ErrorKind::Other)
+    ///     }
+    /// }
+    /// ```
+    fn map_kinds<E>(self, mut f: impl FnMut(Self::ErrorKind) -> E::ErrorKind) -> E
+    where
+        E: ErrorType,
+    {
+        convert::map_kinds(self, &mut f)
+    }
+
+    /// Convert this error into another error type, deciding for every frame
+    /// whether to translate its kind or to nest it, along with its causes,
+    /// as an opaque foreign frame. See [KindMapping].
+    ///
+    /// Frames are visited from the outermost to the innermost, depth-first.
+    /// Nesting stops the conversion for that part of the chain.
+    fn convert_chain<E>(self, mut f: impl FnMut(&Self::ErrorKind) -> KindMapping<E::ErrorKind>) -> E
+    where
+        Self: core::error::Error + Send + Sync + 'static,
+        E: ErrorType,
+    {
+        convert::convert_chain(self, &mut f)
+    }
+}
+
+/// Iterator over the frames of an error chain, from the outermost to the
+/// innermost. Errors with several causes are walked depth-first.
+///
+/// Created by [ErrorType::chain].
+#[derive(Debug)]
+pub struct Chain<'a, E> {
+    stack: Vec<&'a E>,
+}
+
+impl<'a, E> Clone for Chain<'a, E> {
+    fn clone(&self) -> Self {
+        Self {
+            stack: self.stack.clone(),
+        }
+    }
+}
+
+impl<'a, E> Iterator for Chain<'a, E>
+where
+    E: ErrorType,
+{
+    type Item = &'a E;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.stack.pop()?;
+        self.stack.extend(current.causes().iter().rev());
+
+        Some(current)
+    }
+}
+
+impl<'a, E> core::iter::FusedIterator for Chain<'a, E> where E: ErrorType {}
+
+/// Display an error chain on a single line, without its trace context.
+#[doc(hidden)]
+pub fn __display_chain<E>(err: &E, f: &mut core::fmt::Formatter) -> core::fmt::Result
+where
+    E: ErrorType,
+    E::ErrorKind: core::fmt::Display,
+{
+    write!(f, "{}", err.kind())?;
+    match err.causes() {
+        [] => {}
+        [cause] => {
+            write!(f, ": ")?;
+            __display_chain(cause, f)?;
+        }
+        [first, rest @ ..] => {
+            write!(f, ": [")?;
+            __display_chain(first, f)?;
+            for cause in rest {
+                write!(f, "; ")?;
+                __display_chain(cause, f)?;
+            }
+            write!(f, "]")?;
+        }
+    }
+    if let Some(source) = err.foreign_source() {
+        write!(f, ": {source}")?;
+    }
+
+    Ok(())
 }
 
 /// Helper trait to add context to errors.
@@ -178,10 +1314,12 @@ where
     E: ErrorType<ErrorKind = K>,
 {
     fn err_context(self, kind: K) -> Result<T, E> {
-        self.map_err(|x| E::new(kind, Some(Box::new(x.into())), Location::caller()))
+        let location = Location::caller();
+        self.map_err(|x| E::with_causes(kind, vec![x.into()], location))
     }
 
     fn err_context_with(self, f: impl FnOnce() -> K) -> Result<T, E> {
-        self.map_err(|x| E::new(f(), Some(Box::new(x.into())), Location::caller()))
+        let location = Location::caller();
+        self.map_err(|x| E::with_causes(f(), vec![x.into()], location))
     }
 }
@@ -0,0 +1,204 @@
+//! RFC 7807 problem details of error chains.
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::{status::reason_phrase, ErrorStatus, ErrorType};
+
+/// Which frames of an error chain a [ProblemDetails] exposes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FrameVisibility {
+    /// Only frames whose kind is public, see [ErrorStatus::is_public].
+    #[default]
+    Public,
+    /// Every frame. Only meant for internal APIs.
+    All,
+}
+
+/// Policy deciding what a [ProblemDetails] reveals about an error chain.
+///
+/// By default, only public frames are exposed, inner frames are listed and
+/// locations are left out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ProblemPolicy {
+    visibility: FrameVisibility,
+    frames: bool,
+    locations: bool,
+}
+
+impl Default for ProblemPolicy {
+    fn default() -> Self {
+        Self {
+            visibility: FrameVisibility::Public,
+            frames: true,
+            locations: false,
+        }
+    }
+}
+
+impl ProblemPolicy {
+    /// Set which frames are exposed, both as `detail` and in the `frames`
+    /// extension.
+    pub fn visibility(mut self, visibility: FrameVisibility) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
+    /// Enable or disable the `frames` extension listing the inner frames.
+    pub fn frames(mut self, frames: bool) -> Self {
+        self.frames = frames;
+        self
+    }
+
+    /// Enable or disable the locations of the frames in the `frames`
+    /// extension.
+    pub fn locations(mut self, locations: bool) -> Self {
+        self.locations = locations;
+        self
+    }
+
+    fn exposes<K>(&self, kind: &K) -> bool
+    where
+        K: ErrorStatus,
+    {
+        match self.visibility {
+            FrameVisibility::Public => kind.is_public(),
+            FrameVisibility::All => true,
+        }
+    }
+}
+
+/// Problem details of an error chain, as defined by RFC 7807.
+///
+/// `type`, `title` and `status` come from the kind of the outermost frame,
+/// see [ErrorStatus]. `detail` is the message of the outermost frame, and the
+/// `frames` extension lists the inner frames, both following a
+/// [ProblemPolicy]. With the `serde` feature, it serializes as:
+///
+/// ```json
+/// {
+///   "type": "https://example.com/problems/not-found",
+///   "title": "Not Found",
+///   "status": 404,
+///   "detail": "user 42 not found",
+///   "frames": [{"message": "invalid user id", "location": "src/user.rs:10:5"}]
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct ProblemDetails {
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    problem_type: String,
+    title: String,
+    status: u16,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    detail: Option<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    frames: Vec<ProblemFrame>,
+}
+
+impl ProblemDetails {
+    /// Create a new [ProblemDetails] from an error chain with the default
+    /// [ProblemPolicy].
+    pub fn new<E>(err: &E) -> Self
+    where
+        E: ErrorType,
+        E::ErrorKind: ErrorStatus + fmt::Display,
+    {
+        Self::with_policy(err, ProblemPolicy::default())
+    }
+
+    /// Create a new [ProblemDetails] from an error chain with the given
+    /// policy.
+    pub fn with_policy<E>(err: &E, policy: ProblemPolicy) -> Self
+    where
+        E: ErrorType,
+        E::ErrorKind: ErrorStatus + fmt::Display,
+    {
+        let kind = err.kind();
+        let status = kind.http_status();
+        let detail = policy.exposes(kind).then(|| kind.to_string());
+        let frames = if policy.frames {
+            err.chain()
+                .skip(1)
+                .filter(|frame| policy.exposes(frame.kind()))
+                .map(|frame| ProblemFrame {
+                    message: frame.kind().to_string(),
+                    location: policy.locations.then(|| frame.location().to_string()),
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Self {
+            problem_type: kind.problem_type().to_string(),
+            title: kind
+                .title()
+                .unwrap_or_else(|| reason_phrase(status))
+                .to_string(),
+            status,
+            detail,
+            frames,
+        }
+    }
+
+    /// Get the URI identifying the problem type.
+    pub fn problem_type(&self) -> &str {
+        &self.problem_type
+    }
+
+    /// Get the short summary of the problem type.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Get the HTTP status.
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    /// Get the message of the outermost frame, if it is exposed.
+    pub fn detail(&self) -> Option<&str> {
+        self.detail.as_deref()
+    }
+
+    /// Get the exposed inner frames, from the outermost to the innermost.
+    pub fn frames(&self) -> &[ProblemFrame] {
+        &self.frames
+    }
+}
+
+/// Inner frame listed by a [ProblemDetails].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct ProblemFrame {
+    message: String,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    location: Option<String>,
+}
+
+impl ProblemFrame {
+    /// Get the display string of the error kind of this frame.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Get the location of this frame, as `file:line:column`, if locations
+    /// are exposed.
+    pub fn location(&self) -> Option<&str> {
+        self.location.as_deref()
+    }
+}
@@ -0,0 +1,237 @@
+//! Human-friendly rendering of error chains.
+
+use alloc::string::ToString;
+use core::{error::Error as StdError, fmt, panic::Location};
+
+use crate::{ErrorType, TraceContext};
+
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// Multi-line renderer for error chains.
+///
+/// Prints one numbered line per frame, with the location where each frame
+/// was constructed:
+///
+/// ```text
+/// 0: context at src/main.rs:10:5
+/// 1: caused by: base error at src/main.rs:9:5
+/// ```
+///
+/// The attachments of each frame are printed below it, one per line, followed
+/// by the functions recorded by `err_context`:
+///
+/// ```text
+/// 0: context at src/main.rs:10:5
+///    user_id: 42
+///    in app::load at src/main.rs:8:4
+///      path = "config.toml"
+/// ```
+///
+/// With the `minitrace` feature, the trace context of a frame is printed
+/// before its attachments, unless it is the same as the one of the frame it
+/// belongs to:
+///
+/// ```text
+/// 0: context at src/main.rs:10:5
+///    trace: trace_id=0000000000000000000000000000002a span_id=0000000000000007
+/// ```
+///
+/// Chains are not indented, unless they branch: below a frame with more than
+/// one cause, lines are indented by their depth, so the frames of a branch are
+/// told apart from its siblings:
+///
+/// ```text
+/// 0: validation at src/main.rs:12:5
+/// 1: caused by: io error at src/main.rs:10:5
+/// 2:   caused by: no such file
+/// 3: caused by: base error at src/main.rs:11:5
+/// ```
+///
+/// Foreign sources are printed after the frame they belong to, without a
+/// location. The runtime backtrace of the error, if one was captured, is
+/// printed at the end.
+///
+/// The `{:#}` format of an error generated by
+/// [declare_error_type](crate::declare_error_type) renders a [Report] with
+/// the default options.
+#[derive(Debug)]
+pub struct Report<'a, E> {
+    error: &'a E,
+    colored: bool,
+    strip_prefix: Option<&'a str>,
+}
+
+impl<'a, E> Report<'a, E> {
+    /// Create a new [Report] for the given error, without colors.
+    pub fn new(error: &'a E) -> Self {
+        Self {
+            error,
+            colored: false,
+            strip_prefix: None,
+        }
+    }
+
+    /// Enable or disable ANSI colors.
+    pub fn colored(mut self, colored: bool) -> Self {
+        self.colored = colored;
+        self
+    }
+
+    /// Remove the given prefix from the file of every location, such as the
+    /// path of the workspace.
+    pub fn strip_prefix(mut self, prefix: &'a str) -> Self {
+        self.strip_prefix = Some(prefix);
+        self
+    }
+}
+
+impl<'a, E> Clone for Report<'a, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, E> Copy for Report<'a, E> {}
+
+impl<'a, E> Report<'a, E>
+where
+    E: ErrorType,
+    E::ErrorKind: fmt::Display,
+{
+    fn render_frame(
+        &self,
+        f: &mut fmt::Formatter,
+        frame: &E,
+        parent_trace: Option<&TraceContext>,
+        depth: usize,
+        branch_depth: Option<usize>,
+        index: &mut usize,
+    ) -> fmt::Result {
+        let line = Line {
+            depth,
+            branch_depth,
+        };
+        self.render_line(f, line, index, frame.kind(), Some(frame.location()))?;
+        let (dim, reset) = if self.colored { (DIM, RESET) } else { ("", "") };
+        let prefix = if depth > 0 { 2 * line.indent() + 2 } else { 0 };
+        let indent = " ".repeat(index.saturating_sub(1).to_string().len() + prefix + 2);
+        let trace = frame.frame_trace_context();
+        if let Some(trace) = trace.filter(|&x| Some(x) != parent_trace) {
+            write!(f, "\n{indent}{dim}trace:{reset} {trace}")?;
+        }
+        for (key, value) in frame.attachments().iter() {
+            write!(f, "\n{indent}{dim}{key}:{reset} {value:?}")?;
+        }
+        for function in frame.functions() {
+            write!(f, "\n{indent}in {}", function.name())?;
+            self.render_location(f, function.location())?;
+            for (name, value) in function.args() {
+                write!(f, "\n{indent}  {dim}{name} ={reset} {value}")?;
+            }
+        }
+
+        let branch_depth = branch_depth.or((frame.causes().len() > 1).then_some(depth));
+        let first = frame
+            .foreign_source()
+            .map(|x| x as &(dyn StdError + 'static));
+        for source in core::iter::successors(first, |&x| x.source()) {
+            let line = Line {
+                depth: depth + 1,
+                branch_depth,
+            };
+            self.render_line(f, line, index, source, None)?;
+        }
+
+        for cause in frame.causes() {
+            let parent_trace = trace.or(parent_trace);
+            self.render_frame(f, cause, parent_trace, depth + 1, branch_depth, index)?;
+        }
+
+        Ok(())
+    }
+
+    fn render_line(
+        &self,
+        f: &mut fmt::Formatter,
+        line: Line,
+        index: &mut usize,
+        message: &dyn fmt::Display,
+        location: Option<&Location>,
+    ) -> fmt::Result {
+        let (red, yellow, dim, reset) = if self.colored {
+            (RED, YELLOW, DIM, RESET)
+        } else {
+            ("", "", "", "")
+        };
+
+        if *index > 0 {
+            writeln!(f)?;
+        }
+        write!(f, "{dim}{index}:{reset} ")?;
+        if line.depth > 0 {
+            let indent = "  ".repeat(line.indent());
+            write!(f, "{indent}{yellow}caused by:{reset} {message}")?;
+        } else {
+            write!(f, "{red}{message}{reset}")?;
+        }
+        if let Some(location) = location {
+            self.render_location(f, location)?;
+        }
+        *index += 1;
+
+        Ok(())
+    }
+
+    fn render_location(&self, f: &mut fmt::Formatter, location: &Location) -> fmt::Result {
+        let (dim, reset) = if self.colored { (DIM, RESET) } else { ("", "") };
+        let file = location.file();
+        let file = self
+            .strip_prefix
+            .and_then(|prefix| file.strip_prefix(prefix))
+            .map(|x| x.trim_start_matches(['/', '\\']))
+            .unwrap_or(file);
+
+        write!(
+            f,
+            " {dim}at {file}:{}:{}{reset}",
+            location.line(),
+            location.column()
+        )
+    }
+}
+
+/// Position of a rendered line in the chain.
+#[derive(Clone, Copy)]
+struct Line {
+    depth: usize,
+    /// Depth of the outermost frame above the line with more than one cause.
+    branch_depth: Option<usize>,
+}
+
+impl Line {
+    /// Get the indentation level of the line, which is only increased below a
+    /// frame with more than one cause.
+    fn indent(self) -> usize {
+        self.branch_depth
+            .map_or(0, |branch_depth| self.depth - branch_depth - 1)
+    }
+}
+
+impl<'a, E> fmt::Display for Report<'a, E>
+where
+    E: ErrorType,
+    E::ErrorKind: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.render_frame(f, self.error, None, 0, None, &mut 0)?;
+        #[cfg(feature = "std")]
+        if let Some(backtrace) = self.error.backtrace() {
+            write!(f, "\n\nstack backtrace:\n{backtrace}")?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,425 @@
+//! Serialization and deserialization of error chains.
+//!
+//! Errors are serialized as a tree of frames. Each frame carries its kind,
+//! the display string of that kind, its location, its causes, the display
+//! strings of its foreign sources, the debug strings of its attachments and
+//! the functions recorded by `err_context`:
+//!
+//! ```json
+//! {
+//!   "kind": "Context",
+//!   "message": "context",
+//!   "location": {"file": "src/main.rs", "line": 10, "column": 5},
+//!   "causes": [],
+//!   "sources": [],
+//!   "attachments": {"user_id": "42"},
+//!   "functions": [{
+//!     "name": "app::load",
+//!     "location": {"file": "src/main.rs", "line": 8, "column": 4},
+//!     "args": {"path": "\"config.toml\""}
+//!   }]
+//! }
+//! ```
+//!
+//! With the `minitrace` feature, frames which captured a trace context also
+//! carry a `trace` field, with both ids as hexadecimal strings:
+//!
+//! ```json
+//! "trace": {"trace_id": "0000000000000000000000000000002a", "span_id": "0000000000000007"}
+//! ```
+//!
+//! Error types generated by [declare_error_type](crate::declare_error_type)
+//! implement [Serialize] this way when their kind implements [Serialize] and
+//! [Display](fmt::Display). Other implementations of [ErrorType] can use
+//! [serialize] or [Serializable].
+//!
+//! Since a [Location] cannot be built at run time, a received chain is
+//! deserialized into a [RemoteError] instead of the original error type.
+
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{error::Error as StdError, fmt, panic::Location};
+
+use ::serde::{
+    de::Error as _,
+    ser::{SerializeMap, SerializeStruct},
+    Deserialize, Deserializer,
+};
+#[doc(no_inline)]
+pub use ::serde::{Serialize, Serializer};
+
+use crate::{Attachments, ErrorType, FunctionFrame, TraceContext};
+
+/// Serialize an error chain.
+///
+/// Meant to be used with `#[serde(serialize_with = "infinite_errors::serde::serialize")]`.
+pub fn serialize<E, S>(err: &E, serializer: S) -> Result<S::Ok, S::Error>
+where
+    E: ErrorType,
+    E::ErrorKind: Serialize + fmt::Display,
+    S: Serializer,
+{
+    Serializable(err).serialize(serializer)
+}
+
+/// Wrapper implementing [Serialize] for an error chain.
+#[derive(Debug)]
+pub struct Serializable<'a, E>(pub &'a E);
+
+impl<'a, E> Serialize for Serializable<'a, E>
+where
+    E: ErrorType,
+    E::ErrorKind: Serialize + fmt::Display,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let err = self.0;
+        let causes: Vec<_> = err.causes().iter().map(Serializable).collect();
+
+        let functions: Vec<_> = err.functions().iter().map(RemoteFunction::from).collect();
+
+        let mut state = serializer.serialize_struct("Error", 8)?;
+        state.serialize_field("kind", err.kind())?;
+        state.serialize_field("message", &err.kind().to_string())?;
+        state.serialize_field("location", &RemoteLocation::from(err.location()))?;
+        state.serialize_field("causes", &causes)?;
+        state.serialize_field("sources", &foreign_sources(err))?;
+        state.serialize_field("attachments", &SerializableAttachments(err.attachments()))?;
+        state.serialize_field("functions", &functions)?;
+        match err.frame_trace_context() {
+            Some(trace) => state.serialize_field("trace", trace)?,
+            None => state.skip_field("trace")?,
+        }
+        state.end()
+    }
+}
+
+struct SerializableAttachments<'a>(&'a Attachments);
+
+impl<'a> Serialize for SerializableAttachments<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_map(Some(self.0.len()))?;
+        for (key, value) in self.0.iter() {
+            state.serialize_entry(key, &format!("{value:?}"))?;
+        }
+        state.end()
+    }
+}
+
+fn foreign_sources<E>(err: &E) -> Vec<String>
+where
+    E: ErrorType,
+{
+    let first = err.foreign_source().map(|x| x as &(dyn StdError + 'static));
+
+    core::iter::successors(first, |&x| x.source())
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[derive(Serialize, Deserialize)]
+struct RemoteTrace {
+    trace_id: String,
+    span_id: String,
+}
+
+impl Serialize for TraceContext {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        RemoteTrace {
+            trace_id: format!("{:032x}", self.trace_id()),
+            span_id: format!("{:016x}", self.span_id()),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for TraceContext {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let trace = RemoteTrace::deserialize(deserializer)?;
+        let trace_id = u128::from_str_radix(&trace.trace_id, 16).map_err(D::Error::custom)?;
+        let span_id = u64::from_str_radix(&trace.span_id, 16).map_err(D::Error::custom)?;
+
+        Ok(Self::new(trace_id, span_id))
+    }
+}
+
+/// Location of a frame received from another process.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RemoteLocation {
+    file: String,
+    line: u32,
+    column: u32,
+}
+
+impl RemoteLocation {
+    /// Create a new [RemoteLocation].
+    pub fn new(file: impl Into<String>, line: u32, column: u32) -> Self {
+        Self {
+            file: file.into(),
+            line,
+            column,
+        }
+    }
+
+    /// Get the source file.
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    /// Get the line number.
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// Get the column number.
+    pub fn column(&self) -> u32 {
+        self.column
+    }
+}
+
+impl From<&Location<'_>> for RemoteLocation {
+    fn from(location: &Location<'_>) -> Self {
+        Self::new(location.file(), location.line(), location.column())
+    }
+}
+
+impl fmt::Display for RemoteLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+/// Function frame received from another process.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteFunction {
+    name: String,
+    location: RemoteLocation,
+    #[serde(default)]
+    args: BTreeMap<String, String>,
+}
+
+impl RemoteFunction {
+    /// Get the fully qualified name of the function.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the location of the function.
+    pub fn location(&self) -> &RemoteLocation {
+        &self.location
+    }
+
+    /// Get the debug strings of the selected arguments of the function, by
+    /// name.
+    pub fn args(&self) -> &BTreeMap<String, String> {
+        &self.args
+    }
+}
+
+impl From<&FunctionFrame> for RemoteFunction {
+    fn from(function: &FunctionFrame) -> Self {
+        Self {
+            name: function.name().to_string(),
+            location: function.location().into(),
+            args: function
+                .args()
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect(),
+        }
+    }
+}
+
+/// An error chain received from another process.
+///
+/// This is the deserialized form of an error serialized with [serialize] or
+/// [Serializable]. It implements [std::error::Error], so it can be kept as
+/// the foreign source of a local error. Like the original error, it is
+/// displayed on a single line with its whole chain, and its source is its
+/// first cause or, without causes, its first foreign source.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound(deserialize = "K: Deserialize<'de>"))]
+pub struct RemoteError<K> {
+    kind: K,
+    message: String,
+    location: RemoteLocation,
+    #[serde(default)]
+    causes: Vec<RemoteError<K>>,
+    #[serde(default, with = "remote_sources")]
+    sources: Option<Box<RemoteSource>>,
+    #[serde(default)]
+    attachments: BTreeMap<String, String>,
+    #[serde(default)]
+    functions: Vec<RemoteFunction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    trace: Option<TraceContext>,
+}
+
+impl<K> RemoteError<K> {
+    /// Get the error kind of this frame.
+    pub fn kind(&self) -> &K {
+        &self.kind
+    }
+
+    /// Get the display string of the error kind of this frame.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Get the location where this frame was constructed.
+    pub fn location(&self) -> &RemoteLocation {
+        &self.location
+    }
+
+    /// Get the cause for this frame, if one exists. If this frame has more
+    /// than one cause, the first one is returned.
+    pub fn cause(&self) -> Option<&Self> {
+        self.causes.first()
+    }
+
+    /// Get all the causes for this frame.
+    pub fn causes(&self) -> &[Self] {
+        &self.causes
+    }
+
+    /// Get the display strings of the foreign sources of this frame, from the
+    /// outermost to the innermost.
+    pub fn sources(&self) -> impl Iterator<Item = &str> {
+        core::iter::successors(self.sources.as_deref(), |x| x.source.as_deref())
+            .map(RemoteSource::message)
+    }
+
+    /// Get the debug strings of the attachments of this frame, by key.
+    pub fn attachments(&self) -> &BTreeMap<String, String> {
+        &self.attachments
+    }
+
+    /// Get the functions this frame went through, innermost first.
+    pub fn functions(&self) -> &[RemoteFunction] {
+        &self.functions
+    }
+
+    /// Get the trace context captured when this frame was created, if one
+    /// exists.
+    pub fn trace_context(&self) -> Option<&TraceContext> {
+        self.trace.as_ref()
+    }
+}
+
+impl<K> fmt::Display for RemoteError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        match self.causes.as_slice() {
+            [] => {}
+            [cause] => write!(f, ": {cause}")?,
+            [first, rest @ ..] => {
+                write!(f, ": [{first}")?;
+                for cause in rest {
+                    write!(f, "; {cause}")?;
+                }
+                write!(f, "]")?;
+            }
+        }
+        if let Some(source) = &self.sources {
+            write!(f, ": {source}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<K> StdError for RemoteError<K>
+where
+    K: fmt::Debug + 'static,
+{
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self.causes.first() {
+            Some(cause) => Some(cause),
+            None => self
+                .sources
+                .as_deref()
+                .map(|x| x as &(dyn StdError + 'static)),
+        }
+    }
+}
+
+/// Foreign source of a frame received from another process, whose own
+/// source is the next foreign source of the frame.
+#[derive(Debug)]
+pub struct RemoteSource {
+    message: String,
+    source: Option<Box<RemoteSource>>,
+}
+
+impl RemoteSource {
+    /// Get the display string of the foreign source.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for RemoteSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl StdError for RemoteSource {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.source
+            .as_deref()
+            .map(|x| x as &(dyn StdError + 'static))
+    }
+}
+
+/// (De)serialization of the foreign sources of a frame, nested as the sources
+/// of one another, as a list of display strings.
+mod remote_sources {
+    use alloc::{boxed::Box, string::String, vec::Vec};
+
+    use ::serde::{Deserialize, Deserializer, Serializer};
+
+    use super::RemoteSource;
+
+    pub(super) fn serialize<S>(
+        sources: &Option<Box<RemoteSource>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(
+            core::iter::successors(sources.as_deref(), |x| x.source.as_deref())
+                .map(RemoteSource::message),
+        )
+    }
+
+    pub(super) fn deserialize<'de, D>(
+        deserializer: D,
+    ) -> Result<Option<Box<RemoteSource>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let messages = Vec::<String>::deserialize(deserializer)?;
+
+        Ok(messages.into_iter().rev().fold(None, |source, message| {
+            Some(Box::new(RemoteSource { message, source }))
+        }))
+    }
+}
@@ -0,0 +1,224 @@
+//! Mapping of error kinds to HTTP statuses and gRPC codes.
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::ErrorType;
+
+/// gRPC status codes, as defined by the gRPC specification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GrpcCode {
+    /// Not an error.
+    Ok = 0,
+    /// The operation was cancelled, typically by the caller.
+    Cancelled = 1,
+    /// Unknown error.
+    Unknown = 2,
+    /// The client specified an invalid argument.
+    InvalidArgument = 3,
+    /// The deadline expired before the operation could complete.
+    DeadlineExceeded = 4,
+    /// Some requested entity was not found.
+    NotFound = 5,
+    /// The entity the client tried to create already exists.
+    AlreadyExists = 6,
+    /// The caller is not allowed to execute the operation.
+    PermissionDenied = 7,
+    /// Some resource has been exhausted.
+    ResourceExhausted = 8,
+    /// The system is not in a state required for the operation.
+    FailedPrecondition = 9,
+    /// The operation was aborted, typically due to a concurrency issue.
+    Aborted = 10,
+    /// The operation was attempted past the valid range.
+    OutOfRange = 11,
+    /// The operation is not implemented or not supported.
+    Unimplemented = 12,
+    /// Internal error.
+    Internal = 13,
+    /// The service is currently unavailable.
+    Unavailable = 14,
+    /// Unrecoverable data loss or corruption.
+    DataLoss = 15,
+    /// The request does not have valid authentication credentials.
+    Unauthenticated = 16,
+}
+
+impl GrpcCode {
+    /// Get the gRPC code usually matching an HTTP status.
+    pub fn from_http_status(status: u16) -> Self {
+        match status {
+            200..=299 => Self::Ok,
+            400 => Self::InvalidArgument,
+            401 => Self::Unauthenticated,
+            403 => Self::PermissionDenied,
+            404 => Self::NotFound,
+            409 => Self::AlreadyExists,
+            412 => Self::FailedPrecondition,
+            416 => Self::OutOfRange,
+            429 => Self::ResourceExhausted,
+            499 => Self::Cancelled,
+            501 => Self::Unimplemented,
+            503 => Self::Unavailable,
+            504 => Self::DeadlineExceeded,
+            _ if (400..500).contains(&status) => Self::FailedPrecondition,
+            _ if (500..600).contains(&status) => Self::Internal,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// HTTP status and gRPC code of an error kind.
+///
+/// Usually derived with [ErrorKind](crate::ErrorKind) and its
+/// `#[kind(status = 404, grpc = NotFound, public)]` attribute. The
+/// `problem_type` and `title` arguments of the same attribute set the values
+/// used by [ProblemDetails](crate::ProblemDetails).
+pub trait ErrorStatus {
+    /// Get the HTTP status of this error kind.
+    fn http_status(&self) -> u16 {
+        500
+    }
+
+    /// Get the gRPC code of this error kind. Defaults to the code matching
+    /// the HTTP status.
+    fn grpc_code(&self) -> GrpcCode {
+        GrpcCode::from_http_status(self.http_status())
+    }
+
+    /// Whether the message of this error kind can be shown to clients.
+    /// Frames of other kinds are internal and never leave the process.
+    fn is_public(&self) -> bool {
+        false
+    }
+
+    /// Get the URI identifying the problem type of this error kind, as
+    /// defined by RFC 7807.
+    fn problem_type(&self) -> &'static str {
+        "about:blank"
+    }
+
+    /// Get the short summary of the problem type of this error kind. Defaults
+    /// to the reason phrase of the HTTP status.
+    fn title(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+/// Client-facing summary of an error chain.
+///
+/// The status comes from the outermost frame. Only the messages of public
+/// frames, see [ErrorStatus::is_public], are kept, from the outermost to the
+/// innermost. Locations, foreign sources and attachments are left out.
+///
+/// With the `serde` feature, it serializes as:
+///
+/// ```json
+/// {
+///   "status": 404,
+///   "message": "user 42 not found",
+///   "chain": ["user 42 not found"]
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct ErrorBody {
+    status: u16,
+    message: String,
+    chain: Vec<String>,
+}
+
+impl ErrorBody {
+    /// Create a new [ErrorBody] from an error chain.
+    ///
+    /// The message is the one of the outermost public frame, or the reason
+    /// phrase of the status if no frame is public.
+    pub fn new<E>(err: &E) -> Self
+    where
+        E: ErrorType,
+        E::ErrorKind: ErrorStatus + fmt::Display,
+    {
+        let status = err.kind().http_status();
+        let chain: Vec<_> = err
+            .chain()
+            .filter(|frame| frame.kind().is_public())
+            .map(|frame| frame.kind().to_string())
+            .collect();
+        let message = chain
+            .first()
+            .cloned()
+            .unwrap_or_else(|| reason_phrase(status).to_string());
+
+        Self {
+            status,
+            message,
+            chain,
+        }
+    }
+
+    /// Get the HTTP status.
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    /// Get the message of the outermost public frame.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Get the messages of the public frames, from the outermost to the
+    /// innermost.
+    pub fn chain(&self) -> &[String] {
+        &self.chain
+    }
+
+    /// Get the HTTP status as a [StatusCode](http::StatusCode). Invalid
+    /// statuses become `500 Internal Server Error`.
+    #[cfg(feature = "http")]
+    pub fn status_code(&self) -> ::http::StatusCode {
+        ::http::StatusCode::from_u16(self.status)
+            .unwrap_or(::http::StatusCode::INTERNAL_SERVER_ERROR)
+    }
+}
+
+impl fmt::Display for ErrorBody {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.chain.as_slice() {
+            [] => write!(f, "{}", self.message),
+            [first, rest @ ..] => {
+                write!(f, "{first}")?;
+                for message in rest {
+                    write!(f, ": {message}")?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+pub(crate) fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        410 => "Gone",
+        412 => "Precondition Failed",
+        413 => "Payload Too Large",
+        415 => "Unsupported Media Type",
+        422 => "Unprocessable Entity",
+        429 => "Too Many Requests",
+        501 => "Not Implemented",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ if (400..500).contains(&status) => "Client Error",
+        _ => "Internal Server Error",
+    }
+}
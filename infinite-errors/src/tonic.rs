@@ -0,0 +1,31 @@
+//! gRPC statuses for error types, with [tonic].
+//!
+//! With the `tonic` feature, [declare_error_type](crate::declare_error_type)
+//! also implements `From<Error> for tonic::Status` for the generated error
+//! type when its kind implements [ErrorStatus] and
+//! [Display](std::fmt::Display). The status has the gRPC code of the
+//! outermost frame and the messages of the public frames.
+
+use std::fmt;
+
+#[doc(no_inline)]
+pub use ::tonic::{Code, Status};
+
+use crate::{ErrorBody, ErrorStatus, ErrorType, GrpcCode};
+
+/// Build the gRPC status of an error.
+pub fn into_status<E>(err: &E) -> Status
+where
+    E: ErrorType,
+    E::ErrorKind: ErrorStatus + fmt::Display,
+{
+    let code = err.kind().grpc_code();
+
+    Status::new(code.into(), ErrorBody::new(err).to_string())
+}
+
+impl From<GrpcCode> for Code {
+    fn from(code: GrpcCode) -> Self {
+        Code::from_i32(code as i32)
+    }
+}
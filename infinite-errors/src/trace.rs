@@ -0,0 +1,86 @@
+//! Optional capture of the active `minitrace` span.
+
+#[cfg(feature = "minitrace")]
+use alloc::boxed::Box;
+use core::fmt;
+
+/// Trace and span ids of the `minitrace` span active where an error frame
+/// was created.
+///
+/// Displayed as `trace_id=<32 hex digits> span_id=<16 hex digits>`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TraceContext {
+    trace_id: u128,
+    span_id: u64,
+}
+
+impl TraceContext {
+    /// Create a new [TraceContext].
+    pub fn new(trace_id: u128, span_id: u64) -> Self {
+        Self { trace_id, span_id }
+    }
+
+    /// Get the trace and span ids of the local parent span of the current
+    /// thread, if one is set, such as the spans created by `infinite-tracing`.
+    ///
+    /// Always [None] without the `minitrace` feature.
+    #[cfg_attr(not(feature = "minitrace"), inline(always))]
+    pub fn current() -> Option<Self> {
+        #[cfg(feature = "minitrace")]
+        return ::minitrace::collector::SpanContext::current_local_parent()
+            .map(|x| Self::new(x.trace_id.0, x.span_id.0));
+        #[cfg(not(feature = "minitrace"))]
+        None
+    }
+
+    /// Get the trace id.
+    pub fn trace_id(&self) -> u128 {
+        self.trace_id
+    }
+
+    /// Get the span id.
+    pub fn span_id(&self) -> u64 {
+        self.span_id
+    }
+}
+
+impl fmt::Display for TraceContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "trace_id={:032x} span_id={:016x}",
+            self.trace_id, self.span_id
+        )
+    }
+}
+
+/// Trace context captured where an error frame was created.
+///
+/// Only captured when the `minitrace` feature is enabled and a local parent
+/// span is set, see [TraceContext::current]. Otherwise this type is empty.
+#[derive(Clone, Debug, Default)]
+pub struct OriginTrace {
+    #[cfg(feature = "minitrace")]
+    inner: Option<Box<TraceContext>>,
+}
+
+impl OriginTrace {
+    /// Capture the trace context of the current thread, if enabled.
+    #[cfg_attr(not(feature = "minitrace"), inline(always))]
+    pub fn capture() -> Self {
+        #[cfg(feature = "minitrace")]
+        return Self {
+            inner: TraceContext::current().map(Box::new),
+        };
+        #[cfg(not(feature = "minitrace"))]
+        Self {}
+    }
+
+    /// Get the captured trace context, if one exists.
+    pub fn get(&self) -> Option<&TraceContext> {
+        #[cfg(feature = "minitrace")]
+        return self.inner.as_deref();
+        #[cfg(not(feature = "minitrace"))]
+        None
+    }
+}
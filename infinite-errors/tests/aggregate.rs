@@ -0,0 +1,97 @@
+use assert_matches::assert_matches;
+use derive_more::{Display, From};
+
+use infinite_errors::{declare_error_type, CollectErrors};
+
+#[derive(Debug, Display, From, PartialEq)]
+pub enum TestErrorKind {
+    Validation,
+    Field(&'static str),
+    Empty,
+}
+
+declare_error_type!(TestErrorKind);
+
+fn validate(field: &'static str) -> Result<&'static str, Error> {
+    if field.is_empty() {
+        Err(TestErrorKind::Empty)?;
+    }
+    if field.starts_with('_') {
+        Err(Error::from(TestErrorKind::Empty)).err_context(TestErrorKind::Field(field))?;
+    }
+
+    Ok(field)
+}
+
+#[test]
+fn collect_errors_ok() {
+    let values = ["a", "b"].into_iter().map(validate).collect_errors();
+
+    assert_matches!(values.as_deref(), Ok(["a", "b"]));
+}
+
+#[test]
+fn collect_errors_keeps_every_error() {
+    let errors = ["a", "", "_b"]
+        .into_iter()
+        .map(validate)
+        .collect_errors()
+        .unwrap_err();
+
+    assert_eq!(errors.len(), 2);
+    assert_matches!(errors[0].kind(), TestErrorKind::Empty);
+    assert_matches!(errors[1].kind(), TestErrorKind::Field("_b"));
+}
+
+#[test]
+fn err_context_wraps_aggregate_in_one_frame() {
+    let err = ["", "_b"]
+        .into_iter()
+        .map(validate)
+        .collect_errors()
+        .err_context(TestErrorKind::Validation)
+        .unwrap_err();
+
+    assert_matches!(err.kind(), TestErrorKind::Validation);
+    assert_eq!(err.causes().len(), 2);
+    assert_matches!(err.cause().unwrap().kind(), TestErrorKind::Empty);
+    assert_eq!(err.depth(), 3);
+    assert_eq!(
+        err.chain().map(|frame| frame.kind()).collect::<Vec<_>>(),
+        [
+            &TestErrorKind::Validation,
+            &TestErrorKind::Empty,
+            &TestErrorKind::Field("_b"),
+            &TestErrorKind::Empty,
+        ]
+    );
+}
+
+#[test]
+fn aggregate_display_renders_every_branch() {
+    let err = Error::aggregate(
+        TestErrorKind::Validation,
+        [
+            Error::from(TestErrorKind::Empty),
+            validate("_b").unwrap_err(),
+        ],
+    );
+
+    assert_eq!(err.to_string(), "Validation: [Empty; _b: Empty]");
+}
+
+#[test]
+fn aggregate_debug_renders_every_branch() {
+    let err = Error::aggregate(
+        TestErrorKind::Validation,
+        [
+            Error::from(TestErrorKind::Empty),
+            Error::from(TestErrorKind::Field("b")),
+        ],
+    );
+
+    let debug = format!("{err:?}");
+
+    assert!(debug.contains("Empty"));
+    assert!(debug.contains("Field(\"b\")"));
+}
@@ -0,0 +1,177 @@
+#![cfg(feature = "anyhow")]
+
+use assert_matches::assert_matches;
+use derive_more::{Display, From};
+
+use infinite_errors::{
+    anyhow::{ContextFrame, IntoAnyhow},
+    declare_error_type,
+};
+
+#[derive(Debug, Display, From)]
+pub enum TestErrorKind {
+    #[display(fmt = "context")]
+    Context,
+    #[display(fmt = "base")]
+    BaseError,
+}
+
+declare_error_type!(TestErrorKind);
+
+fn fail_anyhow() -> anyhow::Result<()> {
+    Err(anyhow::anyhow!("root")).map_err(|x| x.context("outer"))
+}
+
+#[test]
+fn anyhow_err_context_keeps_chain() {
+    let line = line!() + 2;
+    let err = fail_anyhow()
+        .err_context(TestErrorKind::Context)
+        .unwrap_err();
+
+    assert_matches!(err.kind(), TestErrorKind::Context);
+    assert_eq!(err.location().line(), line);
+    assert_matches!(err.cause(), None);
+
+    let first = err.foreign_source().unwrap() as &(dyn std::error::Error + 'static);
+    let sources: Vec<_> = std::iter::successors(Some(first), |x| x.source())
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(sources, ["outer", "root"]);
+    assert_eq!(err.to_string(), "context: outer");
+}
+
+#[test]
+fn anyhow_err_context_unwraps_same_error_type() {
+    let res: anyhow::Result<()> = Err(Error::from(TestErrorKind::BaseError).into());
+    let err = res.err_context_with(|| TestErrorKind::Context).unwrap_err();
+
+    assert_matches!(err.kind(), TestErrorKind::Context);
+    assert_matches!(err.cause().unwrap().kind(), TestErrorKind::BaseError);
+    assert!(err.foreign_source().is_none());
+}
+
+#[test]
+fn anyhow_err_context_unwraps_same_error_type_below_contexts() {
+    let err = anyhow::Error::from(Error::from(TestErrorKind::BaseError)).context("outer");
+    let err = Err::<(), _>(err)
+        .err_context(TestErrorKind::Context)
+        .unwrap_err();
+
+    assert_matches!(err.kind(), TestErrorKind::Context);
+    assert_matches!(err.cause().unwrap().kind(), TestErrorKind::BaseError);
+    let source = err.foreign_source().unwrap();
+    assert_eq!(source.to_string(), "outer");
+    assert!(source.source().is_none());
+}
+
+#[test]
+fn anyhow_err_context_keeps_error_below_context_of_same_type() {
+    let err =
+        anyhow::anyhow!(std::io::Error::other("io")).context(Error::from(TestErrorKind::BaseError));
+    let err = Err::<(), _>(err)
+        .err_context(TestErrorKind::Context)
+        .unwrap_err();
+
+    assert_matches!(err.cause(), None);
+    let first = err.foreign_source().unwrap() as &(dyn std::error::Error + 'static);
+    let sources: Vec<_> = std::iter::successors(Some(first), |x| x.source())
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(sources, ["base", "io"]);
+}
+
+#[test]
+fn anyhow_err_context_keeps_error_of_same_type_below_context_of_same_type() {
+    let err = anyhow::Error::from(Error::from(TestErrorKind::BaseError))
+        .context(Error::from(TestErrorKind::Context));
+    let err = Err::<(), _>(err)
+        .err_context(TestErrorKind::Context)
+        .unwrap_err();
+
+    assert_matches!(err.cause(), None);
+    let first = err.foreign_source().unwrap() as &(dyn std::error::Error + 'static);
+    let sources: Vec<_> = std::iter::successors(Some(first), |x| x.source())
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(sources, ["context", "base"]);
+}
+
+#[test]
+fn anyhow_err_context_keeps_frames_of_chain() {
+    let context_line = line!() + 2;
+    let err = Result::<(), _>::Err(Error::from(TestErrorKind::BaseError))
+        .err_context(TestErrorKind::Context)
+        .map_err(IntoAnyhow::into_anyhow)
+        .err_context(TestErrorKind::Context)
+        .unwrap_err();
+
+    assert_matches!(err.cause(), None);
+    let frame = err.foreign_source().unwrap();
+    let frame = frame.downcast_ref::<ContextFrame>().unwrap();
+    assert_eq!(frame.message(), "context");
+    assert_eq!(frame.location().unwrap().line(), context_line);
+
+    let base = std::error::Error::source(frame).unwrap();
+    let base = base.downcast_ref::<ContextFrame>().unwrap();
+    assert_eq!(base.message(), "base");
+    assert!(base.location().is_some());
+}
+
+#[test]
+fn anyhow_source_context() {
+    let err = fail_anyhow()
+        .source_context(TestErrorKind::Context)
+        .unwrap_err();
+
+    assert_matches!(err.kind(), TestErrorKind::Context);
+    assert_eq!(err.foreign_source().unwrap().to_string(), "outer");
+}
+
+#[test]
+fn into_anyhow_keeps_frames() {
+    let base_line = line!() + 1;
+    let res: Result<(), Error> = Err(TestErrorKind::BaseError.into());
+    let context_line = line!() + 1;
+    let err = res.err_context(TestErrorKind::Context).unwrap_err();
+
+    let err = err.into_anyhow();
+    let frame = err.downcast_ref::<ContextFrame>().unwrap();
+    assert_eq!(frame.message(), "context");
+    assert_eq!(frame.location().unwrap().line(), context_line);
+
+    let messages: Vec<_> = err.chain().map(ToString::to_string).collect();
+    assert_eq!(messages.len(), 2);
+    assert!(messages[0].starts_with(&format!("context at {}:{context_line}:", file!())));
+    assert!(messages[1].starts_with(&format!("base at {}:{base_line}:", file!())));
+}
+
+#[test]
+fn into_anyhow_keeps_foreign_sources() {
+    let err = Error::with_source(TestErrorKind::Context, std::io::Error::other("io"));
+
+    let messages: Vec<_> = err.into_anyhow().chain().map(ToString::to_string).collect();
+
+    assert_eq!(messages.len(), 2);
+    assert!(messages[0].starts_with("context at "));
+    assert_eq!(messages[1], "io");
+}
+
+#[test]
+fn into_anyhow_walks_every_cause() {
+    let err = Error::aggregate(
+        TestErrorKind::Context,
+        [
+            Error::from(TestErrorKind::BaseError),
+            Error::with_source(TestErrorKind::BaseError, std::io::Error::other("io")),
+        ],
+    );
+
+    let messages: Vec<_> = err.into_anyhow().chain().map(ToString::to_string).collect();
+
+    assert_eq!(messages.len(), 4);
+    assert!(messages[0].starts_with("context at "));
+    assert!(messages[1].starts_with("base at "));
+    assert!(messages[2].starts_with("base at "));
+    assert_eq!(messages[3], "io");
+}
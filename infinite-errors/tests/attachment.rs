@@ -0,0 +1,90 @@
+use derive_more::{Display, From};
+
+use infinite_errors::{declare_error_type, Attach};
+
+#[derive(Debug, Display, From)]
+pub enum TestErrorKind {
+    #[display(fmt = "context")]
+    Context,
+    #[display(fmt = "base error")]
+    BaseError,
+}
+
+declare_error_type!(TestErrorKind);
+
+#[derive(Debug, PartialEq)]
+struct UserId(u64);
+
+fn load(id: u64) -> Result<(), Error> {
+    Err(TestErrorKind::BaseError)
+        .err_context(TestErrorKind::Context)
+        .attach("user_id", UserId(id))
+        .attach_with("retries", || 3u32)
+}
+
+#[test]
+fn attach_to_outermost_frame() {
+    let err = load(42).unwrap_err();
+
+    assert_eq!(err.attachments().len(), 2);
+    assert!(err.cause().unwrap().attachments().is_empty());
+}
+
+#[test]
+fn read_by_key_and_type() {
+    let err = load(42).unwrap_err();
+
+    assert_eq!(
+        err.attachments().get::<UserId>("user_id"),
+        Some(&UserId(42))
+    );
+    assert_eq!(err.attachments().get::<u64>("user_id"), None);
+    assert_eq!(err.attachments().get_by_type::<u32>(), Some(&3));
+    assert_eq!(
+        format!("{:?}", err.attachments().get_by_key("retries").unwrap()),
+        "3"
+    );
+}
+
+#[test]
+fn same_key_replaces_value() {
+    let err = Error::from(TestErrorKind::BaseError)
+        .attach("user_id", 1u64)
+        .attach("user_id", 2u64);
+
+    assert_eq!(err.attachments().len(), 1);
+    assert_eq!(err.attachments().get::<u64>("user_id"), Some(&2));
+}
+
+#[test]
+fn attach_with_is_lazy() {
+    let result = Ok::<_, Error>(()).attach_with("value", || -> u32 { panic!("called") });
+
+    assert!(result.is_ok());
+}
+
+#[test]
+fn debug_shows_attachments() {
+    let err = load(42).unwrap_err();
+
+    assert_eq!(
+        format!("{:?}", err.attachments()),
+        r#"{"user_id": UserId(42), "retries": 3}"#
+    );
+    assert!(format!("{err:?}").contains(r#"attachments: {"user_id": UserId(42), "retries": 3}"#));
+}
+
+#[test]
+fn report_shows_attachments() {
+    let err = load(42).unwrap_err();
+    let report = err.report().to_string();
+    let mut lines = report.lines();
+
+    assert!(lines.next().unwrap().starts_with("0: context at "));
+    assert_eq!(lines.next().unwrap(), "   user_id: UserId(42)");
+    assert_eq!(lines.next().unwrap(), "   retries: 3");
+    assert!(lines
+        .next()
+        .unwrap()
+        .starts_with("1: caused by: base error at "));
+}
@@ -0,0 +1,72 @@
+#![cfg(feature = "axum")]
+
+use futures_executor::block_on;
+use http_body_util::BodyExt;
+
+use infinite_errors::{
+    axum::IntoResponse, declare_error_type, ErrorBody, ErrorKind, ProblemDetails,
+};
+
+#[derive(Debug, ErrorKind)]
+pub enum TestErrorKind {
+    #[error("user {0} not found")]
+    #[kind(status = 404, public)]
+    UserNotFound(u64),
+    #[error("invalid request")]
+    #[kind(status = 400, public)]
+    InvalidRequest,
+    #[error("database query failed at {0}")]
+    Query(&'static str),
+}
+
+declare_error_type!(TestErrorKind);
+
+fn handler() -> Result<(), Error> {
+    let res: Result<(), Error> = Err(TestErrorKind::Query("db.internal:5432").into());
+    res.err_context(TestErrorKind::UserNotFound(42))
+        .err_context(TestErrorKind::InvalidRequest)
+}
+
+#[test]
+fn into_response() {
+    let response = handler().unwrap_err().into_response();
+
+    assert_eq!(response.status(), 400);
+    assert_eq!(response.headers()["content-type"], "application/json");
+
+    let bytes = block_on(response.into_body().collect()).unwrap().to_bytes();
+    let body: ErrorBody = serde_json::from_slice(&bytes).unwrap();
+
+    assert_eq!(body.status(), 400);
+    assert_eq!(body.message(), "invalid request");
+    assert_eq!(body.chain(), ["invalid request", "user 42 not found"]);
+    assert!(!String::from_utf8_lossy(&bytes).contains("db.internal"));
+    assert!(!String::from_utf8_lossy(&bytes).contains(file!()));
+}
+
+#[test]
+fn into_response_internal_error() {
+    let response = Error::from(TestErrorKind::Query("db.internal:5432")).into_response();
+
+    assert_eq!(response.status(), 500);
+    let bytes = block_on(response.into_body().collect()).unwrap().to_bytes();
+    let body: ErrorBody = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(body.message(), "Internal Server Error");
+}
+
+#[test]
+fn problem_details_response() {
+    let response = ProblemDetails::new(&handler().unwrap_err()).into_response();
+
+    assert_eq!(response.status(), 400);
+    assert_eq!(
+        response.headers()["content-type"],
+        "application/problem+json"
+    );
+
+    let bytes = block_on(response.into_body().collect()).unwrap().to_bytes();
+    let problem: ProblemDetails = serde_json::from_slice(&bytes).unwrap();
+
+    assert_eq!(problem.detail(), Some("invalid request"));
+    assert!(!String::from_utf8_lossy(&bytes).contains("db.internal"));
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "backtrace")]
+
+use std::{backtrace::BacktraceStatus, io};
+
+use derive_more::{Display, From};
+
+use infinite_errors::declare_error_type;
+
+#[derive(Debug, Display, From)]
+pub enum TestErrorKind {
+    Context,
+    Io,
+    BaseError,
+}
+
+declare_error_type!(TestErrorKind);
+
+/// Backtrace capture is enabled once, through the environment, before any
+/// error is created. Tests run in parallel threads and capture caches the
+/// variable on first use, so this file must keep a single `#[test]`.
+#[test]
+fn backtraces() {
+    std::env::set_var("RUST_LIB_BACKTRACE", "1");
+
+    backtrace_captured_at_origin();
+    backtrace_captured_for_foreign_source();
+    context_frames_do_not_capture();
+    report_shows_backtrace();
+}
+
+fn backtrace_captured_at_origin() {
+    let err = Result::<(), _>::Err(TestErrorKind::BaseError)
+        .err_context(TestErrorKind::Context)
+        .unwrap_err();
+
+    let backtrace = err.backtrace().unwrap();
+    assert_eq!(backtrace.status(), BacktraceStatus::Captured);
+    assert!(std::ptr::eq(
+        backtrace,
+        err.cause().unwrap().backtrace().unwrap()
+    ));
+}
+
+fn backtrace_captured_for_foreign_source() {
+    let err = Error::with_source(TestErrorKind::Io, io::Error::other("boom"));
+
+    assert!(err.backtrace().is_some());
+}
+
+fn context_frames_do_not_capture() {
+    let err = Result::<(), _>::Err(TestErrorKind::BaseError)
+        .err_context(TestErrorKind::Context)
+        .unwrap_err();
+
+    assert!(infinite_errors::ErrorType::frame_backtrace(&err).is_none());
+    assert!(infinite_errors::ErrorType::frame_backtrace(err.cause().unwrap()).is_some());
+}
+
+fn report_shows_backtrace() {
+    let err = Error::from(TestErrorKind::BaseError);
+
+    assert!(err.report().to_string().contains("\n\nstack backtrace:\n"));
+}
@@ -0,0 +1,83 @@
+use assert_matches::assert_matches;
+use derive_more::{Display, From};
+
+use infinite_errors::{declare_error_type, ErrorType};
+
+#[derive(Debug, Display, From, PartialEq)]
+pub enum TestErrorKind {
+    Outer,
+    Middle,
+    Root(&'static str),
+}
+
+declare_error_type!(TestErrorKind);
+
+fn three_frames() -> Error {
+    Result::<(), _>::Err(TestErrorKind::Root("root"))
+        .err_context(TestErrorKind::Middle)
+        .err_context(TestErrorKind::Outer)
+        .unwrap_err()
+}
+
+#[test]
+fn chain_yields_every_frame() {
+    let err = three_frames();
+
+    let kinds: Vec<_> = err.chain().map(|frame| frame.kind()).collect();
+
+    assert_eq!(
+        kinds,
+        [
+            &TestErrorKind::Outer,
+            &TestErrorKind::Middle,
+            &TestErrorKind::Root("root"),
+        ]
+    );
+    assert!(err.chain().all(|frame| frame.location().file() == file!()));
+}
+
+#[test]
+fn chain_single_frame() {
+    let err = Error::from(TestErrorKind::Root("root"));
+
+    assert_eq!(err.chain().count(), 1);
+    assert_eq!(err.depth(), 1);
+    assert!(std::ptr::eq(err.root_cause(), &err));
+}
+
+#[test]
+fn root_cause() {
+    let err = three_frames();
+
+    assert_matches!(err.root_cause().kind(), TestErrorKind::Root("root"));
+}
+
+#[test]
+fn depth() {
+    assert_eq!(three_frames().depth(), 3);
+    assert_eq!(Error::from_parts(three_frames().into_parts()).depth(), 3);
+}
+
+#[test]
+fn find_kind() {
+    let err = three_frames();
+
+    let frame = err
+        .find_kind(|kind| matches!(kind, TestErrorKind::Middle))
+        .unwrap();
+
+    assert_matches!(frame.kind(), TestErrorKind::Middle);
+    assert_matches!(frame.cause().unwrap().kind(), TestErrorKind::Root(_));
+    assert!(err
+        .find_kind(|kind| matches!(kind, TestErrorKind::Root("other")))
+        .is_none());
+}
+
+#[test]
+fn error_type_trait_is_generic() {
+    fn root_kind<E: ErrorType>(err: &E) -> &E::ErrorKind {
+        err.root_cause().kind()
+    }
+
+    assert_matches!(root_kind(&three_frames()), TestErrorKind::Root("root"));
+}
@@ -0,0 +1,17 @@
+#[test]
+fn err_context() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/err_context/*.rs");
+}
+
+#[test]
+fn error_type() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/error_type/*.rs");
+}
+
+#[test]
+fn error_kind() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/error_kind/*.rs");
+}
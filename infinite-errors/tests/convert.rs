@@ -0,0 +1,93 @@
+use assert_matches::assert_matches;
+
+use infinite_errors::{ErrorType, KindMapping};
+
+mod storage {
+    use derive_more::{Display, From};
+
+    use infinite_errors::declare_error_type;
+
+    #[derive(Debug, Display, From)]
+    pub enum ErrorKind {
+        Query,
+        Connection,
+        NotFound(u64),
+    }
+
+    declare_error_type!(ErrorKind);
+}
+
+mod api {
+    use derive_more::{Display, From};
+
+    use infinite_errors::declare_error_type;
+
+    #[derive(Debug, Display, From)]
+    pub enum ErrorKind {
+        Request,
+        Storage,
+        #[display(fmt = "user {_0} not found")]
+        UserNotFound(u64),
+    }
+
+    declare_error_type!(ErrorKind);
+}
+
+fn storage_error() -> storage::Error {
+    let res: Result<(), storage::Error> = Err(storage::ErrorKind::Connection.into());
+    storage::ErrorContext::err_context(res, storage::ErrorKind::Query).unwrap_err()
+}
+
+fn map_kind(kind: storage::ErrorKind) -> api::ErrorKind {
+    match kind {
+        storage::ErrorKind::NotFound(id) => api::ErrorKind::UserNotFound(id),
+        _ => api::ErrorKind::Storage,
+    }
+}
+
+#[test]
+fn map_kinds_keeps_locations() {
+    let err = storage_error();
+    let locations: Vec<_> = err.chain().map(|x| x.location()).collect();
+
+    let err: api::Error = err.map_kinds(map_kind);
+
+    assert_matches!(err.kind(), api::ErrorKind::Storage);
+    assert_matches!(err.cause().unwrap().kind(), api::ErrorKind::Storage);
+    assert_eq!(
+        err.chain().map(|x| x.location()).collect::<Vec<_>>(),
+        locations
+    );
+}
+
+#[test]
+fn map_kinds_keeps_attachments_and_sources() {
+    let err =
+        storage::Error::with_source(storage::ErrorKind::NotFound(7), std::io::Error::other("io"))
+            .attach("table", "users");
+
+    let err: api::Error = err.map_kinds(map_kind);
+
+    assert_matches!(err.kind(), api::ErrorKind::UserNotFound(7));
+    assert_eq!(err.foreign_source().unwrap().to_string(), "io");
+    assert_eq!(err.attachments().get::<&str>("table"), Some(&"users"));
+}
+
+#[test]
+fn convert_chain_nests_frames() {
+    let err = storage_error();
+    let inner_location = err.cause().unwrap().location();
+
+    let err: api::Error = err.convert_chain(|kind| match kind {
+        storage::ErrorKind::Query => KindMapping::Map(api::ErrorKind::Request),
+        _ => KindMapping::Nest(api::ErrorKind::Storage),
+    });
+
+    assert_matches!(err.kind(), api::ErrorKind::Request);
+    let nested = err.cause().unwrap();
+    assert_matches!(nested.kind(), api::ErrorKind::Storage);
+    assert_eq!(nested.location(), inner_location);
+    assert!(nested.cause().is_none());
+    let source = nested.downcast_source::<storage::Error>().unwrap();
+    assert_matches!(source.kind(), storage::ErrorKind::Connection);
+}
@@ -1,4 +1,7 @@
+use std::future::Future;
+
 use assert_matches::assert_matches;
+use async_trait::async_trait;
 use derive_more::{Display, From};
 
 use infinite_errors::{declare_error_type, err_context};
@@ -8,6 +11,9 @@ const BASE_ERROR_MESSAGE: &str = "test";
 #[derive(Debug, Display, From)]
 pub enum TestErrorKind {
     Context,
+    Override,
+    #[display(fmt = "load {_0}")]
+    Load(String),
     BaseError(&'static str),
 }
 
@@ -93,6 +99,226 @@ fn err_context_macro_async_fn_try_return() {
     assert_correct_error_context(futures_executor::block_on(test()));
 }
 
+#[test]
+fn err_context_macro_impl_future() {
+    #[err_context(TestErrorKind::Context)]
+    fn test() -> impl Future<Output = TestResult> {
+        async { Err(TestErrorKind::BaseError(BASE_ERROR_MESSAGE)) }
+    }
+
+    assert_correct_error_context(futures_executor::block_on(test()));
+}
+
+struct Service;
+
+#[err_context(TestErrorKind::Context)]
+impl Service {
+    fn fail(&self) -> TestResult {
+        Err(TestErrorKind::BaseError(BASE_ERROR_MESSAGE))
+    }
+
+    async fn fail_async(&self) -> TestResult {
+        Err(TestErrorKind::BaseError(BASE_ERROR_MESSAGE))
+    }
+
+    #[err_context(TestErrorKind::Override)]
+    fn fail_override(&self) -> TestResult {
+        Err(TestErrorKind::BaseError(BASE_ERROR_MESSAGE))
+    }
+
+    #[err_context(skip)]
+    fn fail_skip(&self) -> TestResult {
+        Err(TestErrorKind::BaseError(BASE_ERROR_MESSAGE))?
+    }
+
+    fn not_a_result(&self) -> u32 {
+        1
+    }
+}
+
+#[test]
+fn err_context_macro_impl_block() {
+    let service = Service;
+
+    assert_correct_error_context(service.fail());
+    assert_correct_error_context(futures_executor::block_on(service.fail_async()));
+    assert_matches!(
+        service.fail_override().unwrap_err().kind(),
+        TestErrorKind::Override
+    );
+    assert_matches!(
+        service.fail_skip().unwrap_err().kind(),
+        TestErrorKind::BaseError(BASE_ERROR_MESSAGE)
+    );
+    assert_eq!(service.not_a_result(), 1);
+}
+
+#[err_context(TestErrorKind::Context)]
+trait Fallible {
+    fn base(&self) -> TestResult;
+
+    fn fail_default(&self) -> TestResult {
+        self.base()
+    }
+}
+
+impl Fallible for Service {
+    fn base(&self) -> TestResult {
+        Err(TestErrorKind::BaseError(BASE_ERROR_MESSAGE))?
+    }
+}
+
+#[test]
+fn err_context_macro_trait_default_method() {
+    assert_correct_error_context(Service.fail_default());
+}
+
+#[async_trait]
+trait Repository {
+    async fn load(&self) -> TestResult;
+}
+
+#[async_trait]
+#[err_context(TestErrorKind::Context)]
+impl Repository for Service {
+    async fn load(&self) -> TestResult {
+        Err(TestErrorKind::BaseError(BASE_ERROR_MESSAGE))?
+    }
+}
+
+#[test]
+fn err_context_macro_async_trait() {
+    assert_correct_error_context(futures_executor::block_on(Service.load()));
+}
+
+fn consume(path: String) -> TestResult {
+    drop(path);
+    Err(TestErrorKind::BaseError(BASE_ERROR_MESSAGE))?
+}
+
+#[test]
+fn err_context_macro_moved_argument() {
+    #[err_context(TestErrorKind::Load(path), clone(path))]
+    fn load(path: String) -> TestResult {
+        consume(path)
+    }
+
+    #[err_context(TestErrorKind::Load(path), clone(path))]
+    async fn load_async(path: String) -> TestResult {
+        consume(path)
+    }
+
+    assert_matches!(
+        load("a".to_string()).unwrap_err().kind(),
+        TestErrorKind::Load(path) if path == "a"
+    );
+    assert_matches!(
+        futures_executor::block_on(load_async("b".to_string())).unwrap_err().kind(),
+        TestErrorKind::Load(path) if path == "b"
+    );
+}
+
+/// Not `Clone`, so it can only be borrowed by the kind.
+#[derive(Debug)]
+struct Path(String);
+
+fn read(path: &Path) -> TestResult {
+    assert!(!path.0.is_empty());
+    Err(TestErrorKind::BaseError(BASE_ERROR_MESSAGE))?
+}
+
+#[test]
+fn err_context_macro_borrowed_argument() {
+    #[err_context(TestErrorKind::Load(path.0.clone()))]
+    fn load(path: Path) -> TestResult {
+        read(&path)
+    }
+
+    #[err_context(TestErrorKind::Load(path.0.clone()))]
+    async fn load_async(path: Path) -> TestResult {
+        read(&path)
+    }
+
+    assert_matches!(
+        load(Path("a".to_string())).unwrap_err().kind(),
+        TestErrorKind::Load(path) if path == "a"
+    );
+    assert_matches!(
+        futures_executor::block_on(load_async(Path("b".to_string()))).unwrap_err().kind(),
+        TestErrorKind::Load(path) if path == "b"
+    );
+}
+
+struct Counter {
+    id: u32,
+    count: u32,
+}
+
+#[err_context(TestErrorKind::Load(self.id.to_string()))]
+impl Counter {
+    fn bump(&mut self) -> TestResult {
+        self.count += 1;
+        Err(Error::from(TestErrorKind::BaseError(BASE_ERROR_MESSAGE)))
+    }
+
+    async fn bump_async(&mut self) -> TestResult {
+        self.count += 1;
+        Err(Error::from(TestErrorKind::BaseError(BASE_ERROR_MESSAGE)))
+    }
+}
+
+#[test]
+fn err_context_macro_borrowed_self() {
+    let mut counter = Counter { id: 7, count: 0 };
+
+    assert_matches!(
+        counter.bump().unwrap_err().kind(),
+        TestErrorKind::Load(id) if id == "7"
+    );
+    assert_matches!(
+        futures_executor::block_on(counter.bump_async()).unwrap_err().kind(),
+        TestErrorKind::Load(id) if id == "7"
+    );
+    assert_eq!(counter.count, 2);
+}
+
+#[test]
+fn err_context_macro_lazy_kind() {
+    fn make_kind() -> TestErrorKind {
+        panic!("the kind should only be built on error")
+    }
+
+    #[err_context(make_kind())]
+    fn test() -> TestResult {
+        TestResult::Ok(())
+    }
+
+    test().unwrap();
+}
+
+#[test]
+fn err_context_macro_with_function() {
+    fn make_kind() -> TestErrorKind {
+        TestErrorKind::Context
+    }
+
+    #[err_context(with = make_kind)]
+    fn test() -> TestResult {
+        Err(TestErrorKind::BaseError(BASE_ERROR_MESSAGE))
+    }
+
+    #[err_context(with = || TestErrorKind::Load(path), clone(path))]
+    fn test_closure(path: String) -> TestResult {
+        consume(path)
+    }
+
+    assert_correct_error_context(test());
+    assert_matches!(
+        test_closure("c".to_string()).unwrap_err().kind(),
+        TestErrorKind::Load(path) if path == "c"
+    );
+}
+
 fn assert_correct_error_context(res: TestResult) {
     let err = res.unwrap_err();
 
@@ -0,0 +1,104 @@
+use std::io;
+
+use assert_matches::assert_matches;
+
+use infinite_errors::{declare_error_type, ErrorKind, KindMetadata, Severity, VariantName};
+
+#[derive(Debug, ErrorKind)]
+#[kind(code = "E0000")]
+pub enum TestErrorKind {
+    #[error("context")]
+    Context,
+    #[error("failed to load {path}")]
+    #[kind(code = "E0001", severity = Warning)]
+    Load { path: String },
+    #[error("io error")]
+    Io(#[from] io::Error),
+    #[error("invalid value {0:?} (expected {1})")]
+    Invalid(u32, u32),
+    #[error("{} items", count * 2)]
+    Items { count: usize },
+    #[error(transparent)]
+    #[kind(severity = Info)]
+    Parse(#[from] std::num::ParseIntError),
+}
+
+declare_error_type!(TestErrorKind);
+
+#[derive(Debug, ErrorKind)]
+pub enum GenericKind<T: std::fmt::Display> {
+    #[error("value {0}")]
+    Value(T),
+}
+
+fn read() -> Result<(), Error> {
+    Err(io::Error::other("boom"))?;
+
+    Ok(())
+}
+
+fn parse(s: &str) -> Result<u32, Error> {
+    Ok(s.parse()?)
+}
+
+#[test]
+fn display() {
+    assert_eq!(TestErrorKind::Context.to_string(), "context");
+    assert_eq!(
+        TestErrorKind::Load {
+            path: "a.txt".to_string()
+        }
+        .to_string(),
+        "failed to load a.txt"
+    );
+    assert_eq!(
+        TestErrorKind::Invalid(1, 2).to_string(),
+        "invalid value 1 (expected 2)"
+    );
+    assert_eq!(TestErrorKind::Items { count: 2 }.to_string(), "4 items");
+    assert_eq!(GenericKind::Value("x").to_string(), "value x");
+}
+
+#[test]
+fn display_transparent() {
+    let err = parse("x").unwrap_err();
+
+    assert_eq!(err.to_string(), "invalid digit found in string");
+}
+
+#[test]
+fn from_foreign_error() {
+    let err = read().err_context(TestErrorKind::Context).unwrap_err();
+
+    assert_matches!(err.cause().unwrap().kind(), TestErrorKind::Io(x) if x.to_string() == "boom");
+    assert_eq!(err.to_string(), "context: io error");
+}
+
+#[test]
+fn metadata() {
+    let load = TestErrorKind::Load {
+        path: "a.txt".to_string(),
+    };
+
+    assert_eq!(load.code(), Some("E0001"));
+    assert_eq!(load.severity(), Severity::Warning);
+    assert_eq!(TestErrorKind::Context.code(), Some("E0000"));
+    assert_eq!(TestErrorKind::Context.severity(), Severity::Error);
+    assert_eq!(parse("x").unwrap_err().kind().severity(), Severity::Info);
+    assert_eq!(GenericKind::Value(1).code(), None);
+}
+
+#[test]
+fn variant_name() {
+    let load = TestErrorKind::Load {
+        path: "a.txt".to_string(),
+    };
+
+    assert_eq!(VariantName(&load).to_string(), "Load");
+    assert_eq!(
+        VariantName(&TestErrorKind::Invalid(1, 2)).to_string(),
+        "Invalid"
+    );
+    assert_eq!(VariantName(&TestErrorKind::Context).to_string(), "Context");
+    assert_eq!(VariantName(&GenericKind::Value(1)).to_string(), "Value");
+}
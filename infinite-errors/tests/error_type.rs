@@ -0,0 +1,89 @@
+use assert_matches::assert_matches;
+use derive_more::{Display, From};
+
+use infinite_errors::error_type;
+
+mod kinds {
+    use super::*;
+
+    #[derive(Debug, Clone, Display, From)]
+    pub enum Kind {
+        Context,
+        BaseError(&'static str),
+    }
+
+    #[derive(Debug, Display)]
+    pub enum Generic<T> {
+        Value(T),
+    }
+}
+
+/// Error type of the application.
+#[error_type(kind = crate::kinds::Kind, result = AppResult)]
+#[derive(Debug, Clone)]
+pub(crate) struct AppError;
+
+#[error_type(kind = kinds::Generic<u8>, prefix = Other, context = OtherContext)]
+struct OtherError;
+
+fn fails() -> AppResult<()> {
+    Err(kinds::Kind::BaseError("test"))?;
+
+    Ok(())
+}
+
+#[test]
+fn error_type_generated() {
+    let err = fails().err_context(kinds::Kind::Context).unwrap_err();
+
+    assert_matches!(err.kind(), kinds::Kind::Context);
+    assert_matches!(err.cause().unwrap().kind(), kinds::Kind::BaseError("test"));
+    assert_eq!(err.to_string(), "Context: test");
+}
+
+#[test]
+fn two_error_types_in_one_module() {
+    let err: OtherError = Result::<(), OtherError>::Err(OtherError::from(kinds::Generic::Value(1)))
+        .err_context(kinds::Generic::Value(2))
+        .unwrap_err();
+
+    assert_matches!(err.kind(), kinds::Generic::Value(2));
+    assert_eq!(err.depth(), 2);
+}
+
+#[test]
+fn generated_traits_have_default_names() {
+    fn context<T>(res: Result<T, AppError>) -> AppResult<T> {
+        ErrorContext::err_context(res, kinds::Kind::Context)
+    }
+
+    fn source_context<T>(res: Result<T, std::io::Error>) -> Result<T, OtherError> {
+        OtherSourceContext::source_context(res, kinds::Generic::Value(0))
+    }
+
+    assert!(context(fails()).is_err());
+    assert!(source_context::<()>(Err(std::io::Error::other("boom"))).is_err());
+}
+
+#[test]
+fn debug_derive_is_accepted() {
+    let err = AppError::from(kinds::Kind::Context);
+
+    assert!(format!("{err:?}").starts_with("AppError { kind: Context"));
+}
+
+#[test]
+fn clone_derive() {
+    let io_err = std::io::Error::other("boom");
+    let err = Err::<(), _>(AppError::with_source(kinds::Kind::BaseError("io"), io_err))
+        .err_context(kinds::Kind::Context)
+        .unwrap_err();
+    let clone = err.clone();
+
+    assert_eq!(clone.to_string(), err.to_string());
+    assert_eq!(clone.location(), err.location());
+    assert!(std::ptr::eq(
+        clone.cause().unwrap().foreign_source().unwrap(),
+        err.cause().unwrap().foreign_source().unwrap(),
+    ));
+}
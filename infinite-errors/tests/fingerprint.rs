@@ -0,0 +1,118 @@
+use std::panic::Location;
+
+use derive_more::{Display, From};
+
+use infinite_errors::{declare_error_type, ErrorType};
+
+/// Location of the errors with golden fingerprints, which change if it moves.
+fn golden_location() -> &'static Location<'static> {
+    Location::caller()
+}
+
+#[derive(Debug, Display, From)]
+pub enum TestErrorKind {
+    Context,
+    #[display(fmt = "load {_0}")]
+    Load(String),
+    BaseError,
+}
+
+declare_error_type!(TestErrorKind);
+
+mod reordered {
+    use derive_more::{Display, From};
+
+    use infinite_errors::declare_error_type;
+
+    /// The variants of [super::TestErrorKind], in another order.
+    #[derive(Debug, Display, From)]
+    pub enum TestErrorKind {
+        BaseError,
+        #[display(fmt = "load {_0}")]
+        Load(String),
+        Context,
+    }
+
+    declare_error_type!(TestErrorKind);
+}
+
+fn load(path: &str) -> Result<(), Error> {
+    let res: Result<(), Error> = Err(TestErrorKind::BaseError.into());
+    res.err_context(TestErrorKind::Load(path.to_string()))
+}
+
+fn load_twice(path: &str) -> Result<(), Error> {
+    let res: Result<(), Error> = Err(TestErrorKind::BaseError.into());
+    res.err_context(TestErrorKind::Load(path.to_string()))
+}
+
+#[test]
+fn fingerprint_ignores_field_values() {
+    let a = load("a.txt").unwrap_err();
+    let b = load("b.txt").unwrap_err();
+
+    assert_eq!(a.fingerprint(), b.fingerprint());
+    assert_eq!(a.fingerprint().to_string().len(), 16);
+}
+
+#[test]
+fn fingerprint_depends_on_locations() {
+    let a = load("a.txt").unwrap_err();
+    let b = load_twice("a.txt").unwrap_err();
+
+    assert_ne!(a.fingerprint(), b.fingerprint());
+}
+
+#[test]
+fn fingerprint_depends_on_kinds() {
+    fn wrap(kind: TestErrorKind) -> Error {
+        load("a.txt").err_context(kind).unwrap_err()
+    }
+
+    let a = wrap(TestErrorKind::Context);
+    let b = wrap(TestErrorKind::BaseError);
+
+    assert_eq!(a.location(), b.location());
+    assert_ne!(a.fingerprint(), b.fingerprint());
+    assert_eq!(a.fingerprint(), wrap(TestErrorKind::Context).fingerprint());
+}
+
+#[test]
+fn fingerprint_ignores_variant_order() {
+    let location = Location::caller();
+    let fingerprints = |kind: TestErrorKind, reordered: reordered::TestErrorKind| {
+        (
+            Error::new(kind, location).fingerprint(),
+            reordered::Error::new(reordered, location).fingerprint(),
+        )
+    };
+
+    let (a, b) = fingerprints(
+        TestErrorKind::Load("a.txt".to_string()),
+        reordered::TestErrorKind::Load("b.txt".to_string()),
+    );
+    assert_eq!(a, b);
+    let (a, b) = fingerprints(TestErrorKind::Context, reordered::TestErrorKind::Context);
+    assert_eq!(a, b);
+    let (a, b) = fingerprints(TestErrorKind::Context, reordered::TestErrorKind::BaseError);
+    assert_ne!(a, b);
+}
+
+#[test]
+fn fingerprint_golden_values() {
+    let location = golden_location();
+    assert_eq!(
+        (location.file(), location.line()),
+        ("infinite-errors/tests/fingerprint.rs", 9),
+        "golden values depend on this location"
+    );
+
+    let base = Error::new(TestErrorKind::BaseError, location);
+    assert_eq!(base.fingerprint().to_string(), "ea541bd23c56bed0");
+    let err = Error::with_causes(
+        TestErrorKind::Load("a.txt".to_string()),
+        vec![base],
+        location,
+    );
+    assert_eq!(err.fingerprint().value(), 0xd88a35f579ed4411);
+}
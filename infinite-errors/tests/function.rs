@@ -0,0 +1,140 @@
+use std::fmt;
+
+use assert_matches::assert_matches;
+use derive_more::{Display, From};
+
+use infinite_errors::{declare_error_type, err_context};
+
+#[derive(Debug, Display, From)]
+pub enum TestErrorKind {
+    #[display(fmt = "context")]
+    Context,
+    #[display(fmt = "base error")]
+    BaseError,
+    #[display(fmt = "load {_0}")]
+    Load(String),
+}
+
+declare_error_type!(TestErrorKind);
+
+const LOAD_LINE: u32 = line!() + 2;
+#[err_context(function)]
+fn load() -> Result<(), Error> {
+    Err(TestErrorKind::BaseError)
+}
+
+#[err_context(function, args(id, name), clone(name))]
+fn load_with_args(id: u32, name: String) -> Result<(), Error> {
+    drop(name);
+    load()
+}
+
+#[err_context(TestErrorKind::Context, args(id))]
+fn load_with_kind(id: u32) -> Result<(), Error> {
+    load()
+}
+
+/// Panics when formatted.
+struct Unformattable;
+
+impl fmt::Debug for Unformattable {
+    fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
+        panic!("arguments should only be formatted on error")
+    }
+}
+
+#[err_context(function, args(value))]
+fn succeed(value: Unformattable) -> Result<(), Error> {
+    Ok::<_, Error>(())
+}
+
+#[err_context(TestErrorKind::Load(path), args(path), clone(path))]
+fn load_path(path: String) -> Result<(), Error> {
+    drop(path);
+    load()
+}
+
+struct Service;
+
+#[err_context(function)]
+impl Service {
+    fn load(&self) -> Result<(), Error> {
+        Err(TestErrorKind::BaseError)
+    }
+}
+
+#[test]
+fn function_is_recorded() {
+    let err = load().unwrap_err();
+
+    assert_matches!(err.kind(), TestErrorKind::BaseError);
+    assert!(err.cause().is_none());
+
+    let [function] = err.functions() else {
+        panic!("expected a single function frame");
+    };
+    assert_eq!(function.name(), "function::load");
+    assert_eq!(function.location().file(), file!());
+    assert_eq!(function.location().line(), LOAD_LINE);
+    assert!(function.args().is_empty());
+}
+
+#[test]
+fn args_are_recorded() {
+    let err = load_with_args(42, "name".to_string()).unwrap_err();
+
+    let names: Vec<_> = err.functions().iter().map(|x| x.name()).collect();
+    assert_eq!(names, ["function::load", "function::load_with_args"]);
+    assert_eq!(
+        err.functions()[1].args(),
+        [("id", "42".to_string()), ("name", "\"name\"".to_string())]
+    );
+}
+
+#[test]
+fn args_are_only_formatted_on_error() {
+    succeed(Unformattable).unwrap();
+}
+
+#[test]
+fn args_and_kind_share_clones() {
+    let err = load_path("path".to_string()).unwrap_err();
+
+    assert_matches!(err.kind(), TestErrorKind::Load(path) if path == "path");
+    assert_eq!(
+        err.functions()[0].args(),
+        [("path", "\"path\"".to_string())]
+    );
+}
+
+#[test]
+fn function_is_recorded_on_the_new_frame() {
+    let err = load_with_kind(1).unwrap_err();
+
+    assert_matches!(err.kind(), TestErrorKind::Context);
+    assert_eq!(err.functions()[0].name(), "function::load_with_kind");
+    assert_eq!(err.cause().unwrap().functions()[0].name(), "function::load");
+}
+
+#[test]
+fn methods_are_named_after_their_type() {
+    let err = Service.load().unwrap_err();
+
+    assert_eq!(err.functions()[0].name(), "function::Service::load");
+}
+
+#[test]
+fn report_shows_functions() {
+    let err = load_with_args(42, "name".to_string()).unwrap_err();
+    let report = err.report().to_string();
+    let lines: Vec<_> = report.lines().collect();
+
+    assert!(lines[0].starts_with("0: base error at "));
+    assert_eq!(
+        lines[1],
+        format!("   in function::load at {}:{LOAD_LINE}:4", file!())
+    );
+    assert!(lines[2].starts_with("   in function::load_with_args at "));
+    assert_eq!(lines[3], "     id = 42");
+    assert_eq!(lines[4], "     name = \"name\"");
+}
@@ -0,0 +1,138 @@
+#![cfg(feature = "future")]
+
+use assert_matches::assert_matches;
+use derive_more::{Display, From};
+use futures_executor::block_on;
+use futures_util::{stream, StreamExt};
+
+use infinite_errors::declare_error_type;
+
+#[derive(Debug, Clone, Display, From)]
+pub enum TestErrorKind {
+    Context,
+    #[display(fmt = "item {_0}")]
+    Item(u32),
+    BaseError(&'static str),
+}
+
+declare_error_type!(TestErrorKind);
+
+async fn fail() -> Result<(), Error> {
+    Err(Error::from(TestErrorKind::BaseError("test")))
+}
+
+#[test]
+fn future_err_context_ok() {
+    let res = block_on(async { Result::<_, Error>::Ok(1) }.err_context(TestErrorKind::Context));
+
+    assert_eq!(res.unwrap(), 1);
+}
+
+#[test]
+fn future_err_context_err() {
+    let line = line!() + 1;
+    let err = block_on(fail().err_context(TestErrorKind::Context)).unwrap_err();
+
+    assert_matches!(err.kind(), TestErrorKind::Context);
+    assert_eq!(err.location().file(), file!());
+    assert_eq!(err.location().line(), line);
+    assert_matches!(
+        err.cause().unwrap().kind(),
+        TestErrorKind::BaseError("test")
+    );
+    assert_matches!(err.cause().unwrap().cause(), None);
+}
+
+#[test]
+fn future_err_context_with() {
+    let ok = block_on(async { Result::<_, Error>::Ok(()) }.err_context_with(|| {
+        panic!("the kind should only be built on error");
+    }));
+    ok.unwrap();
+
+    let err = block_on(fail().err_context_with(|| TestErrorKind::Context)).unwrap_err();
+
+    assert_matches!(err.kind(), TestErrorKind::Context);
+    assert_matches!(
+        err.cause().unwrap().kind(),
+        TestErrorKind::BaseError("test")
+    );
+}
+
+#[test]
+fn stream_err_context() {
+    let items = vec![
+        Ok(1),
+        Err(Error::from(TestErrorKind::BaseError("first"))),
+        Ok(2),
+        Err(Error::from(TestErrorKind::BaseError("second"))),
+    ];
+
+    let line = line!() + 1;
+    let stream = stream::iter(items).err_context(TestErrorKind::Context);
+    let results: Vec<_> = block_on(stream.collect());
+
+    assert_eq!(results.len(), 4);
+    assert_matches!(results[0], Ok(1));
+    assert_matches!(results[2], Ok(2));
+    for (result, message) in [(&results[1], "first"), (&results[3], "second")] {
+        let err = result.as_ref().unwrap_err();
+        assert_matches!(err.kind(), TestErrorKind::Context);
+        assert_eq!(err.location().line(), line);
+        assert_matches!(err.cause().unwrap().kind(), TestErrorKind::BaseError(x) if *x == message);
+    }
+}
+
+#[test]
+fn stream_err_context_with() {
+    let items = vec![
+        Err(Error::from(TestErrorKind::BaseError("test"))),
+        Ok(1),
+        Err(Error::from(TestErrorKind::BaseError("test"))),
+    ];
+
+    let mut count = 0;
+    let stream = stream::iter(items).err_context_with(|| {
+        count += 1;
+        TestErrorKind::Item(count)
+    });
+    let results: Vec<_> = block_on(stream.collect());
+
+    assert_matches!(
+        results[0].as_ref().unwrap_err().kind(),
+        TestErrorKind::Item(1)
+    );
+    assert_matches!(results[1], Ok(1));
+    assert_matches!(
+        results[2].as_ref().unwrap_err().kind(),
+        TestErrorKind::Item(2)
+    );
+}
+
+#[test]
+fn future_err_context_converts_errors() {
+    let future = async { Result::<(), _>::Err(TestErrorKind::BaseError("test")) };
+    let err = block_on(future.err_context(TestErrorKind::Context)).unwrap_err();
+
+    assert_matches!(err.kind(), TestErrorKind::Context);
+    assert_matches!(
+        err.cause().unwrap().kind(),
+        TestErrorKind::BaseError("test")
+    );
+}
+
+#[test]
+fn stream_err_context_converts_errors() {
+    let items = vec![Ok(1), Err(TestErrorKind::BaseError("test"))];
+
+    let stream = stream::iter(items).err_context_with(|| TestErrorKind::Context);
+    let results: Vec<Result<_, Error>> = block_on(stream.collect());
+
+    assert_matches!(results[0], Ok(1));
+    let err = results[1].as_ref().unwrap_err();
+    assert_matches!(err.kind(), TestErrorKind::Context);
+    assert_matches!(
+        err.cause().unwrap().kind(),
+        TestErrorKind::BaseError("test")
+    );
+}
@@ -0,0 +1,91 @@
+#![cfg(feature = "hooks")]
+
+use std::{
+    sync::{Arc, Mutex},
+    thread::{self, ThreadId},
+};
+
+use derive_more::{Display, From};
+
+use infinite_errors::{declare_error_type, hook};
+
+#[derive(Debug, Display, From, PartialEq)]
+pub enum TestErrorKind {
+    Context,
+    BaseError,
+}
+
+declare_error_type!(TestErrorKind);
+
+type Events = Arc<Mutex<Vec<(String, u32, usize)>>>;
+
+/// Record the events of the current thread only, since tests run in parallel.
+fn record() -> (hook::HookId, Events) {
+    let events = Events::default();
+    let thread: ThreadId = thread::current().id();
+    let recorded = events.clone();
+    let id = hook::add_hook(move |event| {
+        if thread::current().id() == thread {
+            assert!(event.kind::<TestErrorKind>().is_some());
+            recorded.lock().unwrap().push((
+                event.variant_name().to_string(),
+                event.location().line(),
+                event.depth(),
+            ));
+        }
+    });
+
+    (id, events)
+}
+
+#[test]
+fn hooks_see_created_errors() {
+    let (id, events) = record();
+
+    let base_line = line!() + 1;
+    let res: Result<(), Error> = Err(TestErrorKind::BaseError.into());
+    let context_line = line!() + 1;
+    let err = res.err_context(TestErrorKind::Context).unwrap_err();
+    let source_line = line!() + 1;
+    let _ = Error::with_source(TestErrorKind::BaseError, std::io::Error::other("io"));
+
+    assert!(hook::remove_hook(id));
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            ("BaseError".to_string(), base_line, 1),
+            ("Context".to_string(), context_line, 2),
+            ("BaseError".to_string(), source_line, 1),
+        ]
+    );
+    assert_eq!(err.depth(), 2);
+}
+
+#[test]
+fn removed_hooks_do_not_run() {
+    let (id, events) = record();
+    assert!(hook::remove_hook(id));
+    assert!(!hook::remove_hook(id));
+
+    let _ = Error::from(TestErrorKind::BaseError);
+
+    assert!(events.lock().unwrap().is_empty());
+}
+
+#[test]
+fn errors_created_by_hooks_do_not_run_hooks() {
+    let thread = thread::current().id();
+    let count = Arc::new(Mutex::new(0));
+    let counted = count.clone();
+    let id = hook::add_hook(move |_| {
+        if thread::current().id() == thread {
+            *counted.lock().unwrap() += 1;
+            let _ = Error::from(TestErrorKind::Context);
+        }
+    });
+
+    let _ = Error::from(TestErrorKind::BaseError);
+
+    hook::remove_hook(id);
+    assert_eq!(*count.lock().unwrap(), 1);
+}
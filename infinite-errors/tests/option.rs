@@ -0,0 +1,86 @@
+use assert_matches::assert_matches;
+use derive_more::{Display, From};
+
+use infinite_errors::{bail, declare_error_type, ensure};
+
+#[derive(Debug, Display, From)]
+pub enum TestErrorKind {
+    Missing,
+    Zero,
+    Negative,
+}
+
+declare_error_type!(TestErrorKind);
+
+fn check(value: i32) -> Result<i32, Error> {
+    ensure!(value != 0, TestErrorKind::Zero);
+    if value < 0 {
+        bail!(TestErrorKind::Negative);
+    }
+
+    Ok(value)
+}
+
+#[test]
+fn ok_or_context() {
+    assert_matches!(Some(1).ok_or_context(TestErrorKind::Missing), Ok(1));
+
+    let line = line!() + 2;
+    let err = None::<i32>
+        .ok_or_context(TestErrorKind::Missing)
+        .unwrap_err();
+
+    assert_matches!(err.kind(), TestErrorKind::Missing);
+    assert_eq!(err.location().file(), file!());
+    assert_eq!(err.location().line(), line);
+}
+
+#[test]
+fn ok_or_context_with() {
+    let result = Some(1).ok_or_context_with(|| -> TestErrorKind { panic!("called") });
+    assert_matches!(result, Ok(1));
+
+    let line = line!() + 2;
+    let err = None::<i32>
+        .ok_or_context_with(|| TestErrorKind::Missing)
+        .unwrap_err();
+
+    assert_matches!(err.kind(), TestErrorKind::Missing);
+    assert_eq!(err.location().line(), line);
+}
+
+/// Call `ensure!` or `bail!` in a closure, returning the error along with the
+/// line of the call.
+macro_rules! error_and_line {
+    (ensure!($($args:tt)*)) => {
+        ((|| -> Result<(), Error> { ensure!($($args)*); Ok(()) })().unwrap_err(), line!())
+    };
+    (bail!($($args:tt)*)) => {
+        ((|| -> Result<(), Error> { bail!($($args)*) })().unwrap_err(), line!())
+    };
+}
+
+#[test]
+fn ensure_and_bail() {
+    assert_matches!(check(1), Ok(1));
+    assert_matches!(check(0).unwrap_err().kind(), TestErrorKind::Zero);
+    assert_matches!(check(-1).unwrap_err().kind(), TestErrorKind::Negative);
+}
+
+#[test]
+fn ensure_location() {
+    let (err, line) = error_and_line!(ensure!(1 < 0, TestErrorKind::Negative));
+
+    assert_matches!(err.kind(), TestErrorKind::Negative);
+    assert_eq!(err.location().file(), file!());
+    assert_eq!(err.location().line(), line);
+}
+
+#[test]
+fn bail_location() {
+    let (err, line) = error_and_line!(bail!(TestErrorKind::Zero));
+
+    assert_matches!(err.kind(), TestErrorKind::Zero);
+    assert_eq!(err.location().file(), file!());
+    assert_eq!(err.location().line(), line);
+}
@@ -0,0 +1,98 @@
+use infinite_errors::{
+    declare_error_type, ErrorKind, FrameVisibility, ProblemDetails, ProblemPolicy,
+};
+
+#[derive(Debug, ErrorKind)]
+pub enum TestErrorKind {
+    #[error("user {0} not found")]
+    #[kind(status = 404, public)]
+    #[kind(problem_type = "https://example.com/problems/not-found")]
+    UserNotFound(u64),
+    #[error("invalid user id")]
+    #[kind(status = 400, public, title = "Invalid Id")]
+    InvalidId,
+    #[error("query failed on db.internal")]
+    Query,
+}
+
+declare_error_type!(TestErrorKind);
+
+fn error() -> Error {
+    let res: Result<(), Error> = Err(TestErrorKind::Query.into());
+    res.err_context(TestErrorKind::InvalidId)
+        .err_context(TestErrorKind::UserNotFound(42))
+        .unwrap_err()
+}
+
+#[test]
+fn problem_details_default_policy() {
+    let problem = ProblemDetails::new(&error());
+
+    assert_eq!(
+        problem.problem_type(),
+        "https://example.com/problems/not-found"
+    );
+    assert_eq!(problem.title(), "Not Found");
+    assert_eq!(problem.status(), 404);
+    assert_eq!(problem.detail(), Some("user 42 not found"));
+    assert_eq!(problem.frames().len(), 1);
+    assert_eq!(problem.frames()[0].message(), "invalid user id");
+    assert_eq!(problem.frames()[0].location(), None);
+}
+
+#[test]
+fn problem_details_all_frames_with_locations() {
+    let err = error();
+    let policy = ProblemPolicy::default()
+        .visibility(FrameVisibility::All)
+        .locations(true);
+
+    let problem = ProblemDetails::with_policy(&err, policy);
+
+    let messages: Vec<_> = problem.frames().iter().map(|x| x.message()).collect();
+    assert_eq!(messages, ["invalid user id", "query failed on db.internal"]);
+    assert_eq!(
+        problem.frames()[1].location(),
+        Some(err.root_cause().location().to_string().as_str())
+    );
+}
+
+#[test]
+fn problem_details_internal_error() {
+    let err = Error::from(TestErrorKind::Query);
+
+    let problem = ProblemDetails::with_policy(&err, ProblemPolicy::default().frames(false));
+
+    assert_eq!(problem.problem_type(), "about:blank");
+    assert_eq!(problem.title(), "Internal Server Error");
+    assert_eq!(problem.status(), 500);
+    assert_eq!(problem.detail(), None);
+    assert!(problem.frames().is_empty());
+}
+
+#[test]
+fn problem_details_title() {
+    let err = Error::from(TestErrorKind::InvalidId);
+
+    let problem = ProblemDetails::new(&err);
+
+    assert_eq!(problem.title(), "Invalid Id");
+    assert_eq!(problem.status(), 400);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn problem_details_json() {
+    let problem = ProblemDetails::new(&error());
+
+    assert_eq!(
+        serde_json::to_value(&problem).unwrap(),
+        serde_json::json!({
+            "type": "https://example.com/problems/not-found",
+            "title": "Not Found",
+            "status": 404,
+            "detail": "user 42 not found",
+            "frames": [{"message": "invalid user id"}],
+        })
+    );
+}
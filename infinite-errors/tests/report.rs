@@ -0,0 +1,135 @@
+use std::io;
+
+use derive_more::{Display, From};
+
+use infinite_errors::{declare_error_type, Report};
+
+#[derive(Debug, Display, From)]
+pub enum TestErrorKind {
+    #[display(fmt = "context")]
+    Context,
+    #[display(fmt = "validation")]
+    Validation,
+    #[display(fmt = "io error")]
+    Io,
+    #[display(fmt = "base error: {_0}")]
+    BaseError(&'static str),
+}
+
+declare_error_type!(TestErrorKind);
+
+/// Render a report without the runtime backtrace, which is only there when the
+/// `backtrace` feature is enabled.
+fn frames(report: Report<'_, Error>) -> String {
+    let rendered = report.to_string();
+
+    match rendered.split_once("\n\nstack backtrace:\n") {
+        Some((frames, _)) => frames.to_string(),
+        None => rendered,
+    }
+}
+
+fn chain() -> (Error, u32) {
+    let line = line!() + 2;
+    let err = Result::<(), _>::Err(TestErrorKind::BaseError("test"))
+        .err_context(TestErrorKind::Context)
+        .unwrap_err();
+
+    (err, line)
+}
+
+#[test]
+fn report() {
+    let (err, line) = chain();
+
+    assert_eq!(
+        frames(err.report()),
+        format!(
+            "0: context at {file}:{line}:10\n\
+             1: caused by: base error: test at {file}:{line}:10",
+            file = file!(),
+        )
+    );
+}
+
+#[test]
+fn alternate_display_is_report() {
+    let (err, _) = chain();
+
+    assert_eq!(format!("{err:#}"), err.report().to_string());
+    assert_eq!(format!("{err}"), "context: base error: test");
+}
+
+#[test]
+fn report_strip_prefix() {
+    let (err, line) = chain();
+
+    assert_eq!(
+        frames(err.report().strip_prefix("infinite-errors")),
+        format!(
+            "0: context at tests/report.rs:{line}:10\n\
+             1: caused by: base error: test at tests/report.rs:{line}:10"
+        )
+    );
+}
+
+#[test]
+fn report_colored() {
+    let (err, line) = chain();
+
+    assert_eq!(
+        frames(err.report().colored(true)),
+        format!(
+            "\x1b[2m0:\x1b[0m \x1b[31mcontext\x1b[0m \x1b[2mat {file}:{line}:10\x1b[0m\n\
+             \x1b[2m1:\x1b[0m \x1b[33mcaused by:\x1b[0m base error: test \x1b[2mat {file}:{line}:10\x1b[0m",
+            file = file!(),
+        )
+    );
+}
+
+#[test]
+fn report_tree_and_foreign_sources() {
+    let line = line!() + 4;
+    let err = Error::aggregate(
+        TestErrorKind::Validation,
+        [
+            Error::with_source(TestErrorKind::Io, io::Error::other("boom")),
+            Error::from(TestErrorKind::BaseError("test")),
+        ],
+    );
+
+    assert_eq!(
+        frames(err.report().strip_prefix("infinite-errors/tests/")),
+        format!(
+            "0: validation at report.rs:{}:15\n\
+             1: caused by: io error at report.rs:{line}:13\n\
+             2:   caused by: boom\n\
+             3: caused by: base error: test at report.rs:{}:13",
+            line - 3,
+            line + 1,
+        )
+    );
+}
+
+#[test]
+fn report_linear_chain_is_not_indented() {
+    let line = line!() + 2;
+    let err = Result::<(), _>::Err(io::Error::other("boom"))
+        .source_context(TestErrorKind::Io)
+        .err_context(TestErrorKind::Validation)
+        .err_context(TestErrorKind::Context)
+        .unwrap_err();
+
+    assert_eq!(
+        frames(err.report().strip_prefix("infinite-errors/tests/")),
+        format!(
+            "0: context at report.rs:{}:10\n\
+             1: caused by: validation at report.rs:{}:10\n\
+             2: caused by: io error at report.rs:{}:10\n\
+             3: caused by: boom",
+            line + 2,
+            line + 1,
+            line,
+        )
+    );
+}
@@ -0,0 +1,181 @@
+#![cfg(feature = "serde")]
+
+use std::{error::Error as _, io};
+
+use assert_matches::assert_matches;
+use derive_more::{Display, From};
+use serde::{Deserialize, Serialize};
+
+use infinite_errors::{
+    declare_error_type,
+    serde::{RemoteError, Serializable},
+    Attach,
+};
+
+#[derive(Debug, Display, From, Serialize, Deserialize)]
+pub enum TestErrorKind {
+    #[display(fmt = "context")]
+    Context,
+    #[display(fmt = "io error")]
+    Io,
+    #[display(fmt = "base error: {_0}")]
+    BaseError(String),
+}
+
+declare_error_type!(TestErrorKind);
+
+#[derive(Serialize)]
+struct Response {
+    #[serde(serialize_with = "infinite_errors::serde::serialize")]
+    error: Error,
+}
+
+fn chain() -> (Error, u32) {
+    let line = line!() + 2;
+    let err = Result::<(), _>::Err(TestErrorKind::BaseError("test".to_string()))
+        .err_context(TestErrorKind::Context)
+        .unwrap_err();
+
+    (err, line)
+}
+
+#[test]
+fn serialize_frames() {
+    let (err, line) = chain();
+
+    let value = serde_json::to_value(Serializable(&err)).unwrap();
+
+    assert_eq!(
+        value,
+        serde_json::json!({
+            "kind": "Context",
+            "message": "context",
+            "location": {"file": file!(), "line": line, "column": 10},
+            "causes": [{
+                "kind": {"BaseError": "test"},
+                "message": "base error: test",
+                "location": {"file": file!(), "line": line, "column": 10},
+                "causes": [],
+                "sources": [],
+                "attachments": {},
+                "functions": [],
+            }],
+            "sources": [],
+            "attachments": {},
+            "functions": [],
+        })
+    );
+}
+
+#[test]
+fn serialize_with() {
+    let (error, _) = chain();
+
+    let value = serde_json::to_value(Response { error }).unwrap();
+
+    assert_eq!(value["error"]["kind"], "Context");
+    assert_eq!(value["error"]["causes"][0]["message"], "base error: test");
+}
+
+#[test]
+fn serialize_foreign_sources() {
+    let err = Error::with_source(TestErrorKind::Io, io::Error::other("boom"));
+
+    let value = serde_json::to_value(Serializable(&err)).unwrap();
+
+    assert_eq!(value["sources"], serde_json::json!(["boom"]));
+}
+
+#[test]
+fn serialize_error_type() {
+    let (err, _) = chain();
+
+    assert_eq!(
+        serde_json::to_value(&err).unwrap(),
+        serde_json::to_value(Serializable(&err)).unwrap()
+    );
+}
+
+#[test]
+fn remote_source_reaches_foreign_sources() {
+    #[derive(Debug, Display)]
+    #[display(fmt = "outer")]
+    struct Outer(io::Error);
+
+    impl std::error::Error for Outer {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    let err = Result::<(), _>::Err(Outer(io::Error::other("boom")))
+        .source_context(TestErrorKind::Io)
+        .err_context(TestErrorKind::Context)
+        .unwrap_err();
+    let json = serde_json::to_string(&err).unwrap();
+    let remote: RemoteError<TestErrorKind> = serde_json::from_str(&json).unwrap();
+
+    let messages = |first: Option<&(dyn std::error::Error + 'static)>| {
+        std::iter::successors(first, |&x| x.source())
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(messages(remote.source()), messages(err.source()));
+    assert_eq!(
+        messages(remote.source()),
+        ["io error: outer", "outer", "boom"]
+    );
+    assert_eq!(remote.to_string(), err.to_string());
+    assert_eq!(
+        remote.cause().unwrap().sources().collect::<Vec<_>>(),
+        ["outer", "boom"]
+    );
+}
+
+#[test]
+fn deserialize_remote_chain() {
+    let (err, line) = chain();
+    let json = serde_json::to_string(&Serializable(&err)).unwrap();
+
+    let remote: RemoteError<TestErrorKind> = serde_json::from_str(&json).unwrap();
+
+    assert_matches!(remote.kind(), TestErrorKind::Context);
+    assert_eq!(remote.message(), "context");
+    assert_eq!(remote.location().file(), file!());
+    assert_eq!(remote.location().line(), line);
+    let cause = remote.cause().unwrap();
+    assert_matches!(cause.kind(), TestErrorKind::BaseError(x) if x == "test");
+    assert_eq!(cause.location().line(), line);
+    assert!(cause.causes().is_empty());
+    assert_eq!(remote.to_string(), err.to_string());
+}
+
+#[test]
+fn remote_chain_as_foreign_source() {
+    let (err, _) = chain();
+    let json = serde_json::to_string(&Serializable(&err)).unwrap();
+    let remote: RemoteError<TestErrorKind> = serde_json::from_str(&json).unwrap();
+
+    let err = Error::with_source(TestErrorKind::Io, remote);
+
+    assert_eq!(err.to_string(), "io error: context: base error: test");
+    assert_matches!(
+        err.downcast_source::<RemoteError<TestErrorKind>>()
+            .unwrap()
+            .kind(),
+        TestErrorKind::Context
+    );
+}
+
+#[test]
+fn attachments_roundtrip() {
+    let err = Result::<(), Error>::Err(TestErrorKind::Io.into())
+        .attach("user_id", 42u64)
+        .unwrap_err();
+
+    let value = serde_json::to_value(Serializable(&err)).unwrap();
+    assert_eq!(value["attachments"], serde_json::json!({"user_id": "42"}));
+
+    let remote: RemoteError<TestErrorKind> = serde_json::from_value(value).unwrap();
+    assert_eq!(remote.attachments()["user_id"], "42");
+}
@@ -0,0 +1,119 @@
+use std::{error::Error as _, fmt, io};
+
+use assert_matches::assert_matches;
+use derive_more::{Display, From};
+
+use infinite_errors::{declare_error_type, ErrorType};
+
+#[derive(Debug, Display, From)]
+pub enum TestErrorKind {
+    Context,
+    Io,
+}
+
+declare_error_type!(TestErrorKind);
+
+#[derive(Debug)]
+struct Outer(io::Error);
+
+impl fmt::Display for Outer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "outer")
+    }
+}
+
+impl std::error::Error for Outer {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+fn read() -> Result<(), Error> {
+    Err(Outer(io::Error::new(io::ErrorKind::NotFound, "missing"))).source_context(TestErrorKind::Io)
+}
+
+#[test]
+fn source_context_keeps_foreign_error() {
+    let err = read().unwrap_err();
+
+    assert_matches!(err.kind(), TestErrorKind::Io);
+    assert!(err.cause().is_none());
+    let outer = err.downcast_source::<Outer>().unwrap();
+    assert_eq!(outer.0.kind(), io::ErrorKind::NotFound);
+    assert!(err.downcast_source::<io::Error>().is_none());
+    assert!(ErrorType::foreign_source(&err).is_some());
+}
+
+#[test]
+fn source_context_with() {
+    let err = Result::<(), _>::Err(io::Error::other("boom"))
+        .source_context_with(|| TestErrorKind::Io)
+        .unwrap_err();
+
+    assert_eq!(
+        err.downcast_source::<io::Error>().unwrap().to_string(),
+        "boom"
+    );
+}
+
+#[test]
+fn std_error_source_reaches_foreign_chain() {
+    let err = read().err_context(TestErrorKind::Context).unwrap_err();
+
+    let sources: Vec<String> = std::iter::successors(err.source(), |&x| x.source())
+        .map(|x| x.to_string())
+        .collect();
+
+    assert_eq!(sources, ["Io: outer", "outer", "missing"]);
+}
+
+#[test]
+fn display_shows_foreign_source() {
+    let err = read().err_context(TestErrorKind::Context).unwrap_err();
+
+    assert_eq!(err.to_string(), "Context: Io: outer");
+}
+
+#[test]
+fn source_walker_repeats_display_chain() {
+    let err = read().err_context(TestErrorKind::Context).unwrap_err();
+
+    // The way `anyhow` and `eyre` print an error
+    let mut rendered = err.to_string();
+    let mut source = err.source();
+    while let Some(x) = source {
+        rendered.push_str(&format!("\nCaused by: {x}"));
+        source = x.source();
+    }
+
+    assert_eq!(
+        rendered,
+        "Context: Io: outer\nCaused by: Io: outer\nCaused by: outer\nCaused by: missing"
+    );
+
+    let report = format!("{err:#}");
+    assert_eq!(report.matches("Io").count(), 1);
+    assert_eq!(report.matches("outer").count(), 1);
+    assert_eq!(report.matches("missing").count(), 1);
+}
+
+#[test]
+fn location_is_the_caller() {
+    let line = line!() + 2;
+    let err = Result::<(), _>::Err(io::Error::other("boom"))
+        .source_context(TestErrorKind::Io)
+        .err_context(TestErrorKind::Context)
+        .unwrap_err();
+
+    assert_eq!(err.location().file(), file!());
+    assert_eq!(err.location().line(), line + 1);
+    assert_eq!(err.cause().unwrap().location().line(), line);
+}
+
+#[test]
+fn with_source() {
+    let err = Error::with_source(TestErrorKind::Io, "plain message");
+
+    assert_eq!(err.to_string(), "Io: plain message");
+    assert_eq!(err.location().line(), line!() - 3);
+}
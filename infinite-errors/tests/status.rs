@@ -0,0 +1,102 @@
+use assert_matches::assert_matches;
+
+use infinite_errors::{declare_error_type, ErrorBody, ErrorKind, ErrorStatus, GrpcCode};
+
+#[derive(Debug, ErrorKind)]
+pub enum TestErrorKind {
+    #[error("user {0} not found")]
+    #[kind(status = 404, public)]
+    UserNotFound(u64),
+    #[error("invalid request")]
+    #[kind(status = 400, grpc = FailedPrecondition, public)]
+    InvalidRequest,
+    #[error("database query failed")]
+    Query,
+    #[error("service unavailable")]
+    #[kind(status = 503)]
+    Unavailable,
+}
+
+declare_error_type!(TestErrorKind);
+
+/// Without status metadata, `ErrorStatus` is not derived and can be
+/// implemented by hand.
+#[derive(Debug, ErrorKind)]
+pub enum ManualKind {
+    #[error("teapot")]
+    Teapot,
+}
+
+impl ErrorStatus for ManualKind {
+    fn http_status(&self) -> u16 {
+        418
+    }
+}
+
+#[test]
+fn derived_status() {
+    assert_eq!(TestErrorKind::UserNotFound(1).http_status(), 404);
+    assert_eq!(
+        TestErrorKind::UserNotFound(1).grpc_code(),
+        GrpcCode::NotFound
+    );
+    assert!(TestErrorKind::UserNotFound(1).is_public());
+
+    assert_eq!(TestErrorKind::InvalidRequest.http_status(), 400);
+    assert_eq!(
+        TestErrorKind::InvalidRequest.grpc_code(),
+        GrpcCode::FailedPrecondition
+    );
+
+    assert_eq!(TestErrorKind::Query.http_status(), 500);
+    assert_eq!(TestErrorKind::Query.grpc_code(), GrpcCode::Internal);
+    assert!(!TestErrorKind::Query.is_public());
+
+    assert_eq!(
+        TestErrorKind::Unavailable.grpc_code(),
+        GrpcCode::Unavailable
+    );
+}
+
+#[test]
+fn grpc_code_from_http_status() {
+    assert_eq!(GrpcCode::from_http_status(401), GrpcCode::Unauthenticated);
+    assert_eq!(
+        GrpcCode::from_http_status(422),
+        GrpcCode::FailedPrecondition
+    );
+    assert_eq!(GrpcCode::from_http_status(502), GrpcCode::Internal);
+    assert_eq!(GrpcCode::from_http_status(600), GrpcCode::Unknown);
+}
+
+#[test]
+fn error_body_hides_internal_frames() {
+    let res: Result<(), Error> = Err(TestErrorKind::Query.into());
+    let err = res
+        .err_context(TestErrorKind::UserNotFound(42))
+        .unwrap_err();
+
+    let body = ErrorBody::new(&err);
+
+    assert_eq!(body.status(), 404);
+    assert_eq!(body.message(), "user 42 not found");
+    assert_eq!(body.chain(), ["user 42 not found"]);
+    assert_eq!(body.to_string(), "user 42 not found");
+}
+
+#[test]
+fn error_body_without_public_frames() {
+    let err = Error::from(TestErrorKind::Unavailable);
+
+    let body = ErrorBody::new(&err);
+
+    assert_eq!(body.status(), 503);
+    assert_eq!(body.message(), "Service Unavailable");
+    assert_matches!(body.chain(), []);
+}
+
+#[test]
+fn manual_status() {
+    assert_eq!(ManualKind::Teapot.http_status(), 418);
+    assert!(!ManualKind::Teapot.is_public());
+}
@@ -0,0 +1,40 @@
+#![cfg(feature = "tonic")]
+
+use infinite_errors::{
+    declare_error_type,
+    tonic::{Code, Status},
+    ErrorKind,
+};
+
+#[derive(Debug, ErrorKind)]
+pub enum TestErrorKind {
+    #[error("user {0} not found")]
+    #[kind(status = 404, public)]
+    UserNotFound(u64),
+    #[error("database query failed at {0}")]
+    #[kind(grpc = Unavailable)]
+    Query(&'static str),
+}
+
+declare_error_type!(TestErrorKind);
+
+#[test]
+fn into_status() {
+    let res: Result<(), Error> = Err(TestErrorKind::Query("db.internal:5432").into());
+    let err = res
+        .err_context(TestErrorKind::UserNotFound(42))
+        .unwrap_err();
+
+    let status = Status::from(err);
+
+    assert_eq!(status.code(), Code::NotFound);
+    assert_eq!(status.message(), "user 42 not found");
+}
+
+#[test]
+fn into_status_internal_error() {
+    let status: Status = Error::from(TestErrorKind::Query("db.internal:5432")).into();
+
+    assert_eq!(status.code(), Code::Unavailable);
+    assert_eq!(status.message(), "Internal Server Error");
+}
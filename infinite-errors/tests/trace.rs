@@ -0,0 +1,83 @@
+#![cfg(feature = "minitrace")]
+
+use derive_more::Display;
+use std::sync::Once;
+
+use minitrace::{
+    collector::{Config, ConsoleReporter, SpanContext, SpanId, TraceId},
+    Span,
+};
+
+use infinite_errors::declare_error_type;
+
+#[derive(Debug, Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TestErrorKind {
+    Context,
+    BaseError,
+}
+
+declare_error_type!(TestErrorKind);
+
+fn fail() -> Result<(), Error> {
+    Err(TestErrorKind::BaseError.into())
+}
+
+/// Install a reporter, without which spans are no-ops and have no context.
+fn set_reporter() {
+    static REPORTER: Once = Once::new();
+    REPORTER.call_once(|| minitrace::set_reporter(ConsoleReporter, Config::default()));
+}
+
+#[test]
+fn error_without_span_has_no_trace_context() {
+    let err = fail().unwrap_err();
+
+    assert_eq!(err.trace_context(), None);
+    assert_eq!(err.to_string(), "BaseError");
+}
+
+#[test]
+fn error_captures_local_parent_span() {
+    set_reporter();
+    let root = Span::root("test", SpanContext::new(TraceId(42), SpanId::default()));
+    let _guard = root.set_local_parent();
+    let span_id = SpanContext::current_local_parent().unwrap().span_id.0;
+
+    let err = fail().err_context(TestErrorKind::Context).unwrap_err();
+
+    let trace = err.trace_context().unwrap();
+    assert_eq!(trace.trace_id(), 42);
+    assert_eq!(trace.span_id(), span_id);
+    assert_eq!(err.cause().unwrap().trace_context(), Some(trace));
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Context: BaseError [trace_id=0000000000000000000000000000002a span_id={span_id:016x}]"
+        ),
+    );
+    assert_eq!(
+        err.report().to_string().matches("trace: trace_id=").count(),
+        1
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serialized_error_keeps_trace_context() {
+    use infinite_errors::serde::{RemoteError, Serializable};
+
+    set_reporter();
+    let root = Span::root("test", SpanContext::new(TraceId(42), SpanId::default()));
+    let _guard = root.set_local_parent();
+
+    let err = fail().unwrap_err();
+    let value = serde_json::to_value(Serializable(&err)).unwrap();
+    assert_eq!(
+        value["trace"]["trace_id"],
+        "0000000000000000000000000000002a"
+    );
+
+    let remote: RemoteError<TestErrorKind> = serde_json::from_value(value).unwrap();
+    assert_eq!(remote.trace_context(), err.trace_context());
+}
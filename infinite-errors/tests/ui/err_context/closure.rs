@@ -0,0 +1,15 @@
+use derive_more::{Display, From};
+use infinite_errors::{declare_error_type, err_context};
+
+#[derive(Debug, Display, From)]
+pub enum ErrorKind {
+    Context,
+}
+
+declare_error_type!(ErrorKind);
+
+fn main() {
+    let f = #[err_context(ErrorKind::Context)]
+    || -> Result<(), Error> { Ok(()) };
+    let _ = f();
+}
@@ -0,0 +1,18 @@
+use derive_more::{Display, From};
+use infinite_errors::{declare_error_type, err_context};
+
+#[derive(Debug, Display, From)]
+pub enum ErrorKind {
+    Context,
+}
+
+declare_error_type!(ErrorKind);
+
+#[err_context]
+fn missing_kind() -> Result<(), Error> {
+    Ok(())
+}
+
+fn main() {
+    let _ = missing_kind();
+}
@@ -0,0 +1,14 @@
+use derive_more::{Display, From};
+use infinite_errors::{declare_error_type, err_context};
+
+#[derive(Debug, Display, From)]
+pub enum ErrorKind {
+    Context,
+}
+
+declare_error_type!(ErrorKind);
+
+#[err_context(ErrorKind::Context)]
+struct NotAFunction;
+
+fn main() {}
@@ -0,0 +1,14 @@
+use infinite_errors::ErrorKind;
+
+#[derive(Debug, ErrorKind)]
+pub enum Kind {
+    #[error("{f}")]
+    Named { f: u32 },
+    #[error("{f} and {formatter}")]
+    Both { f: u32, formatter: u32 },
+}
+
+fn main() {
+    assert_eq!(Kind::Named { f: 1 }.to_string(), "1");
+    assert_eq!(Kind::Both { f: 1, formatter: 2 }.to_string(), "1 and 2");
+}
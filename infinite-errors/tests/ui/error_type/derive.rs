@@ -0,0 +1,13 @@
+use derive_more::Display;
+use infinite_errors::error_type;
+
+#[derive(Debug, Display)]
+pub enum ErrorKind {
+    Context,
+}
+
+#[error_type(kind = ErrorKind)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppError;
+
+fn main() {}
@@ -1,13 +1,14 @@
 use crate::parameters::{MacroArgs, ReturnLogOptions};
 use proc_macro2::{Ident, Span};
+use quote::{quote, ToTokens};
+use syn::ext::IdentExt;
 use syn::punctuated::Punctuated;
 use syn::token::Comma;
-use syn::{ItemFn, LitStr};
+use syn::visit_mut::{self, VisitMut};
+use syn::{FnArg, ItemFn, LitStr, Pat, PatIdent, ReturnType, Type};
 
 pub fn instrument(parameters: MacroArgs, fn_item: ItemFn) -> proc_macro2::TokenStream {
     let ingress_log_level: Option<LitStr> = None; //Some(LitStr::new("info", Span::call_site()));
-    let ok_result_log_level = LitStr::new("info", Span::call_site());
-    let err_result_log_level = LitStr::new("error", Span::call_site());
 
     let minitrace_annotation_tokens = quote!(
         #[::minitrace::trace]
@@ -19,42 +20,27 @@ pub fn instrument(parameters: MacroArgs, fn_item: ItemFn) -> proc_macro2::TokenS
     let skip_options: Option<Vec<String>> = parameters
         .log_parameters
         .then_some(parameters.parameters_to_skip);
-    let ingress_params = if let Some(ingress_log_level) = ingress_log_level {
-        quote!(ingress=#ingress_log_level)
-    } else {
-        quote!()
-    };
-    let degress_params = match parameters.log_return {
-        ReturnLogOptions::Skip => {
+    let logcall_annotation_tokens = if let Some(ingress_log_level) = ingress_log_level {
+        let skip_params = if let Some(to_skip) = &skip_options {
+            let skip_punctuated_list: Punctuated<Ident, Comma> = to_skip
+                .iter()
+                .map(|param_name| Ident::new(param_name, Span::call_site()))
+                .collect();
+            quote!(skip=[#skip_punctuated_list], )
+        } else {
+            // no `skip_options` list present means that no input params will be logged for the function call
             quote!()
-        }
-        ReturnLogOptions::LogErrOnly => {
-            quote!(err=#err_result_log_level,)
-        }
-        ReturnLogOptions::LogOkOnly => {
-            quote!(ok=#ok_result_log_level,)
-        }
-        ReturnLogOptions::LogAnyRet => {
-            quote!(egress=#ok_result_log_level,)
-        }
-    };
-    let skip_params = if let Some(to_skip) = skip_options {
-        let skip_punctuated_list: Punctuated<Ident, Comma> = to_skip
-            .into_iter()
-            .map(|param_name| Ident::new(&param_name, Span::call_site()))
-            .collect();
-        quote!(skip=[#skip_punctuated_list], )
+        };
+        quote!(
+            #[::logcall::logcall(ingress=#ingress_log_level, #skip_params)]
+        )
     } else {
-        // no `skip_options` list present means that no input params will be logged for the function call
         quote!()
     };
 
-    let logcall_annotation_tokens = if degress_params.is_empty() && ingress_params.is_empty() {
-        quote!()
-    } else {
-        quote!(
-            #[::logcall::logcall(#degress_params #ingress_params #skip_params)]
-        )
+    let fn_item = match parameters.log_return {
+        ReturnLogOptions::Skip => fn_item,
+        log_return => log_return_value(fn_item, log_return, skip_options.as_deref()),
     };
 
     quote!(
@@ -64,6 +50,119 @@ pub fn instrument(parameters: MacroArgs, fn_item: ItemFn) -> proc_macro2::TokenS
     )
 }
 
+/// Wraps the body of `fn_item` so that its value is bound once and logged as the function returns
+/// -- according to `log_return` --, along with the parameters not in `skip_options` (see
+/// [instrument]).\
+/// If an `Err` holding an `infinite-errors` error is logged, its chain is built from the returned
+/// value and given to the log record, which emits it as the structured `error_chain` rather than
+/// as the `Debug` string in `ret`.\
+/// `impl Trait`s are replaced by `_` when annotating the body with the return type.
+fn log_return_value(
+    mut fn_item: ItemFn,
+    log_return: ReturnLogOptions,
+    skip_options: Option<&[String]>,
+) -> ItemFn {
+    let level = match log_return {
+        ReturnLogOptions::LogErrOnly => quote!(Error),
+        _ => quote!(Info),
+    };
+    let name = fn_item.sig.ident.to_string();
+    let params: Vec<_> = fn_item
+        .sig
+        .inputs
+        .iter()
+        .filter_map(|input| {
+            let FnArg::Typed(input) = input else {
+                // receivers are never logged
+                return None;
+            };
+            let Pat::Ident(PatIdent { ident, .. }) = &*input.pat else {
+                let pattern = input.pat.to_token_stream().to_string();
+                return Some(quote!(.skipped_param(#pattern)));
+            };
+            let param_name = ident.unraw().to_string();
+            let is_logged = skip_options.is_some_and(|to_skip| !to_skip.contains(&param_name));
+            Some(if is_logged {
+                quote!(.param(#param_name, &#ident))
+            } else {
+                quote!(.skipped_param(#param_name))
+            })
+        })
+        .collect();
+    let params_tokens = if params.is_empty() {
+        quote!()
+    } else {
+        quote!(.map(|__infinite_tracing_call| __infinite_tracing_call #(#params)*))
+    };
+
+    let mut return_type = match &fn_item.sig.output {
+        ReturnType::Type(_, return_type) => return_type.clone(),
+        ReturnType::Default => Box::new(syn::parse_quote!(())),
+    };
+    ImplTraitEraser.visit_type_mut(&mut return_type);
+    let block = &fn_item.block;
+    let call_block_tokens = if fn_item.sig.asyncness.is_some() {
+        quote!(::infinite_tracing::__private::typed_future::<#return_type, _>(async move #block).await)
+    } else {
+        quote!(::infinite_tracing::__private::call_once::<#return_type, _>(move || #block))
+    };
+
+    let log_error_chain_tokens = quote!(
+        #[allow(unused_imports, clippy::needless_borrow)]
+        {
+            use ::infinite_tracing::__private::ErrorChainResult as _;
+            use ::infinite_tracing::__private::NotErrorChainResult as _;
+            __infinite_tracing_call.log_return(
+                &__infinite_tracing_ret,
+                (&__infinite_tracing_ret).__infinite_tracing_error_chain(),
+            );
+        }
+    );
+    let log_tokens = match log_return {
+        ReturnLogOptions::LogErrOnly => quote!(
+            if let ::core::result::Result::Err(_) = &__infinite_tracing_ret {
+                #log_error_chain_tokens
+            }
+        ),
+        ReturnLogOptions::LogOkOnly => quote!(if let ::core::result::Result::Ok(_) =
+            &__infinite_tracing_ret
+        {
+            __infinite_tracing_call
+                .log_return(&__infinite_tracing_ret, ::core::option::Option::None);
+        }),
+        _ => log_error_chain_tokens,
+    };
+
+    fn_item.block = syn::parse_quote!({
+        let __infinite_tracing_call = ::infinite_tracing::__private::FnCall::start(
+            ::infinite_tracing::__private::Level::#level,
+            #name,
+            ::core::module_path!(),
+            ::core::file!(),
+            ::core::line!(),
+        )#params_tokens;
+        let __infinite_tracing_ret = #call_block_tokens;
+        if let ::core::option::Option::Some(__infinite_tracing_call) = __infinite_tracing_call {
+            #log_tokens
+        }
+        __infinite_tracing_ret
+    });
+    fn_item
+}
+
+/// Replaces every `impl Trait` of a type with `_`, leaving it to be inferred
+struct ImplTraitEraser;
+
+impl VisitMut for ImplTraitEraser {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        if let Type::ImplTrait(_) = ty {
+            *ty = syn::parse_quote!(_);
+        } else {
+            visit_mut::visit_type_mut(self, ty);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -87,18 +186,22 @@ mod tests {
         );
     }
 
-    /// By specifying "Log Err Results", the logging of params is also activated for `logcall`.
+    /// By specifying "Log Err Results", the returned `Err`s are logged along with the params.
     /// ```nocompile
     /// #[instrument(err)]
     #[test]
     fn log_result_and_params_on_err() {
-        let expected_fn_def = r#"# [:: minitrace :: trace] # [:: logcall :: logcall (err = "error" , skip = [] ,)] fn a () { }"#;
+        let expected_fn_def = r#"# [:: minitrace :: trace] fn a (a : u32) -> Result < u32 , u32 > { let __infinite_tracing_call = :: infinite_tracing :: __private :: FnCall :: start (:: infinite_tracing :: __private :: Level :: Error , "a" , :: core :: module_path ! () , :: core :: file ! () , :: core :: line ! () ,) . map (| __infinite_tracing_call | __infinite_tracing_call . param ("a" , & a)) ; let __infinite_tracing_ret = :: infinite_tracing :: __private :: call_once :: < Result < u32 , u32 > , _ > (move | | { Err (a) }) ; if let :: core :: option :: Option :: Some (__infinite_tracing_call) = __infinite_tracing_call { if let :: core :: result :: Result :: Err (_) = & __infinite_tracing_ret { # [allow (unused_imports , clippy :: needless_borrow)] { use :: infinite_tracing :: __private :: ErrorChainResult as _ ; use :: infinite_tracing :: __private :: NotErrorChainResult as _ ; __infinite_tracing_call . log_return (& __infinite_tracing_ret , (& __infinite_tracing_ret) . __infinite_tracing_error_chain () ,) ; } } } __infinite_tracing_ret }"#;
         let parameters = MacroArgs {
             log_return: ReturnLogOptions::LogErrOnly,
             log_parameters: true,
             ..MacroArgs::default()
         };
-        let fn_tokens = test_fn_item();
+        let fn_tokens = syn::parse_quote!(
+            fn a(a: u32) -> Result<u32, u32> {
+                Err(a)
+            }
+        );
         let observed_fn_def = instrument(parameters, fn_tokens).to_string();
         assert_eq!(
             observed_fn_def, expected_fn_def,
@@ -106,19 +209,22 @@ mod tests {
         );
     }
 
-    /// By specifying "skipall", the logging of params is deactivated for `logcall`.
+    /// By specifying "skipall", no params get logged.
     /// ```nocompile
     /// #[instrument(err, skip_all)]
     #[test]
     fn log_result_but_no_params_on_err() {
-        let expected_fn_def =
-            r#"# [:: minitrace :: trace] # [:: logcall :: logcall (err = "error" ,)] fn a () { }"#;
+        let expected_fn_def = r#"# [:: minitrace :: trace] fn a (a : u32) -> Result < u32 , u32 > { let __infinite_tracing_call = :: infinite_tracing :: __private :: FnCall :: start (:: infinite_tracing :: __private :: Level :: Error , "a" , :: core :: module_path ! () , :: core :: file ! () , :: core :: line ! () ,) . map (| __infinite_tracing_call | __infinite_tracing_call . skipped_param ("a")) ; let __infinite_tracing_ret = :: infinite_tracing :: __private :: call_once :: < Result < u32 , u32 > , _ > (move | | { Err (a) }) ; if let :: core :: option :: Option :: Some (__infinite_tracing_call) = __infinite_tracing_call { if let :: core :: result :: Result :: Err (_) = & __infinite_tracing_ret { # [allow (unused_imports , clippy :: needless_borrow)] { use :: infinite_tracing :: __private :: ErrorChainResult as _ ; use :: infinite_tracing :: __private :: NotErrorChainResult as _ ; __infinite_tracing_call . log_return (& __infinite_tracing_ret , (& __infinite_tracing_ret) . __infinite_tracing_error_chain () ,) ; } } } __infinite_tracing_ret }"#;
         let parameters = MacroArgs {
             log_return: ReturnLogOptions::LogErrOnly,
             log_parameters: false,
             ..MacroArgs::default()
         };
-        let fn_tokens = test_fn_item();
+        let fn_tokens = syn::parse_quote!(
+            fn a(a: u32) -> Result<u32, u32> {
+                Err(a)
+            }
+        );
         let observed_fn_def = instrument(parameters, fn_tokens).to_string();
         assert_eq!(
             observed_fn_def, expected_fn_def,
@@ -126,12 +232,12 @@ mod tests {
         );
     }
 
-    /// By specifying a `parameters_to_skip` list, `logcall` should be informed of it.
+    /// By specifying a `parameters_to_skip` list, only the other params get logged.
     /// ```nocompile
     /// #[instrument(err, skip(password,secret))]
     #[test]
     fn log_result_and_some_params_on_err() {
-        let expected_fn_def = r#"# [:: minitrace :: trace] # [:: logcall :: logcall (err = "error" , skip = [password , secret] ,)] fn a () { }"#;
+        let expected_fn_def = r#"# [:: minitrace :: trace] fn a (a : u32 , password : & str , secret : & str) -> Result < u32 , u32 > { let __infinite_tracing_call = :: infinite_tracing :: __private :: FnCall :: start (:: infinite_tracing :: __private :: Level :: Error , "a" , :: core :: module_path ! () , :: core :: file ! () , :: core :: line ! () ,) . map (| __infinite_tracing_call | __infinite_tracing_call . param ("a" , & a) . skipped_param ("password") . skipped_param ("secret")) ; let __infinite_tracing_ret = :: infinite_tracing :: __private :: call_once :: < Result < u32 , u32 > , _ > (move | | { Err (a) }) ; if let :: core :: option :: Option :: Some (__infinite_tracing_call) = __infinite_tracing_call { if let :: core :: result :: Result :: Err (_) = & __infinite_tracing_ret { # [allow (unused_imports , clippy :: needless_borrow)] { use :: infinite_tracing :: __private :: ErrorChainResult as _ ; use :: infinite_tracing :: __private :: NotErrorChainResult as _ ; __infinite_tracing_call . log_return (& __infinite_tracing_ret , (& __infinite_tracing_ret) . __infinite_tracing_error_chain () ,) ; } } } __infinite_tracing_ret }"#;
         let parameters = MacroArgs {
             log_return: ReturnLogOptions::LogErrOnly,
             log_parameters: true,
@@ -141,7 +247,87 @@ mod tests {
                 .collect(),
             ..MacroArgs::default()
         };
-        let fn_tokens = test_fn_item();
+        let fn_tokens = syn::parse_quote!(
+            fn a(a: u32, password: &str, secret: &str) -> Result<u32, u32> {
+                Err(a)
+            }
+        );
+        let observed_fn_def = instrument(parameters, fn_tokens).to_string();
+        assert_eq!(
+            observed_fn_def, expected_fn_def,
+            "Function definition attributes mismatch"
+        );
+    }
+
+    /// `async` bodies are awaited with their output tied to the return type, and any returned
+    /// value is logged.
+    /// ```nocompile
+    /// #[instrument(ret)]
+    /// async fn a() -> Result<u32, Error> { Ok(1) }
+    #[test]
+    fn log_async_returned_values() {
+        let expected_fn_def = r#"# [:: minitrace :: trace] async fn a () -> Result < u32 , Error > { let __infinite_tracing_call = :: infinite_tracing :: __private :: FnCall :: start (:: infinite_tracing :: __private :: Level :: Info , "a" , :: core :: module_path ! () , :: core :: file ! () , :: core :: line ! () ,) ; let __infinite_tracing_ret = :: infinite_tracing :: __private :: typed_future :: < Result < u32 , Error > , _ > (async move { Ok (1) }) . await ; if let :: core :: option :: Option :: Some (__infinite_tracing_call) = __infinite_tracing_call { # [allow (unused_imports , clippy :: needless_borrow)] { use :: infinite_tracing :: __private :: ErrorChainResult as _ ; use :: infinite_tracing :: __private :: NotErrorChainResult as _ ; __infinite_tracing_call . log_return (& __infinite_tracing_ret , (& __infinite_tracing_ret) . __infinite_tracing_error_chain () ,) ; } } __infinite_tracing_ret }"#;
+        let parameters = MacroArgs {
+            log_return: ReturnLogOptions::LogAnyRet,
+            log_parameters: false,
+            ..MacroArgs::default()
+        };
+        let fn_tokens = syn::parse_quote!(
+            async fn a() -> Result<u32, Error> {
+                Ok(1)
+            }
+        );
+        let observed_fn_def = instrument(parameters, fn_tokens).to_string();
+        assert_eq!(
+            observed_fn_def, expected_fn_def,
+            "Function definition attributes mismatch"
+        );
+    }
+
+    /// `impl Trait`s of the return type are left to be inferred when wrapping the body.
+    /// ```nocompile
+    /// #[instrument(err)]
+    /// fn a() -> Result<impl Display, Error> { Ok(1) }
+    #[test]
+    fn impl_trait_returns_are_inferred() {
+        let expected_fn_def = r#"# [:: minitrace :: trace] fn a () -> Result < impl Display , Error > { let __infinite_tracing_call = :: infinite_tracing :: __private :: FnCall :: start (:: infinite_tracing :: __private :: Level :: Error , "a" , :: core :: module_path ! () , :: core :: file ! () , :: core :: line ! () ,) ; let __infinite_tracing_ret = :: infinite_tracing :: __private :: call_once :: < Result < _ , Error > , _ > (move | | { Ok (1) }) ; if let :: core :: option :: Option :: Some (__infinite_tracing_call) = __infinite_tracing_call { if let :: core :: result :: Result :: Err (_) = & __infinite_tracing_ret { # [allow (unused_imports , clippy :: needless_borrow)] { use :: infinite_tracing :: __private :: ErrorChainResult as _ ; use :: infinite_tracing :: __private :: NotErrorChainResult as _ ; __infinite_tracing_call . log_return (& __infinite_tracing_ret , (& __infinite_tracing_ret) . __infinite_tracing_error_chain () ,) ; } } } __infinite_tracing_ret }"#;
+        let parameters = MacroArgs {
+            log_return: ReturnLogOptions::LogErrOnly,
+            log_parameters: false,
+            ..MacroArgs::default()
+        };
+        let fn_tokens = syn::parse_quote!(
+            fn a() -> Result<impl Display, Error> {
+                Ok(1)
+            }
+        );
+        let observed_fn_def = instrument(parameters, fn_tokens).to_string();
+        assert_eq!(
+            observed_fn_def, expected_fn_def,
+            "Function definition attributes mismatch"
+        );
+    }
+
+    /// When only `Ok` values are logged, no error chain is looked for. Receivers are never logged.
+    /// ```nocompile
+    /// #[instrument(ok)]
+    /// fn get(&mut self) -> Result<&mut Vec<u8>, Error> { .. }
+    #[test]
+    fn log_ok_results_only() {
+        let expected_fn_def = r#"# [:: minitrace :: trace] fn get (& mut self) -> Result < & mut Vec < u8 > , Error > { let __infinite_tracing_call = :: infinite_tracing :: __private :: FnCall :: start (:: infinite_tracing :: __private :: Level :: Info , "get" , :: core :: module_path ! () , :: core :: file ! () , :: core :: line ! () ,) ; let __infinite_tracing_ret = :: infinite_tracing :: __private :: call_once :: < Result < & mut Vec < u8 > , Error > , _ > (move | | { if self . bytes . is_empty () { return Err (Kind :: Empty . into ()) ; } Ok (& mut self . bytes) }) ; if let :: core :: option :: Option :: Some (__infinite_tracing_call) = __infinite_tracing_call { if let :: core :: result :: Result :: Ok (_) = & __infinite_tracing_ret { __infinite_tracing_call . log_return (& __infinite_tracing_ret , :: core :: option :: Option :: None) ; } } __infinite_tracing_ret }"#;
+        let parameters = MacroArgs {
+            log_return: ReturnLogOptions::LogOkOnly,
+            log_parameters: true,
+            ..MacroArgs::default()
+        };
+        let fn_tokens = syn::parse_quote!(
+            fn get(&mut self) -> Result<&mut Vec<u8>, Error> {
+                if self.bytes.is_empty() {
+                    return Err(Kind::Empty.into());
+                }
+                Ok(&mut self.bytes)
+            }
+        );
         let observed_fn_def = instrument(parameters, fn_tokens).to_string();
         assert_eq!(
             observed_fn_def, expected_fn_def,
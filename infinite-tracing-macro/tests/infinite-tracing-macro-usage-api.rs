@@ -33,7 +33,7 @@ fn log_result_and_params_on_err() {
     }
 
     let expected_logs = [
-        "{\"logging.googleapis.com/sourceLocation\":{\"FILE\":\"infinite-tracing-macro/tests/infinite-tracing-macro-usage-api.rs\",\"LINE\":\"30\"},\"message\":\"do_something(a: 11) => Err(11)\",\"severity\":\"ERROR\",\"span\":{\"a\":\"11\",\"module\":\"infinite_tracing_macro_usage_api\",\"ret\":\"Err(11)\"},\"target\":\"test method\"",
+        "{\"@type\":\"type.googleapis.com/google.devtools.clouderrorreporting.v1beta1.ReportedErrorEvent\",\"context\":{\"reportLocation\":{\"filePath\":\"infinite-tracing-macro/tests/infinite-tracing-macro-usage-api.rs\",\"lineNumber\":30}},\"logging.googleapis.com/sourceLocation\":{\"FILE\":\"infinite-tracing-macro/tests/infinite-tracing-macro-usage-api.rs\",\"LINE\":\"30\"},\"message\":\"do_something(a: 11) => Err(11)\",\"severity\":\"ERROR\",\"span\":{\"a\":\"11\",\"module\":\"infinite_tracing_macro_usage_api\",\"ret\":\"Err(11)\"},\"target\":\"test method\"",
     ];
 
     let collect_logs = follow_logs();
@@ -53,7 +53,7 @@ fn log_result_but_no_params_on_err() {
     }
 
     let expected_logs = [
-        "{\"logging.googleapis.com/sourceLocation\":{\"FILE\":\"infinite-tracing-macro/tests/infinite-tracing-macro-usage-api.rs\",\"LINE\":\"50\"},\"message\":\"do_something(a: <skipped>) => Err(12)\",\"severity\":\"ERROR\",\"span\":{\"module\":\"infinite_tracing_macro_usage_api\",\"ret\":\"Err(12)\"},\"target\":\"test method\"",
+        "{\"@type\":\"type.googleapis.com/google.devtools.clouderrorreporting.v1beta1.ReportedErrorEvent\",\"context\":{\"reportLocation\":{\"filePath\":\"infinite-tracing-macro/tests/infinite-tracing-macro-usage-api.rs\",\"lineNumber\":50}},\"logging.googleapis.com/sourceLocation\":{\"FILE\":\"infinite-tracing-macro/tests/infinite-tracing-macro-usage-api.rs\",\"LINE\":\"50\"},\"message\":\"do_something(a: <skipped>) => Err(12)\",\"severity\":\"ERROR\",\"span\":{\"module\":\"infinite_tracing_macro_usage_api\",\"ret\":\"Err(12)\"},\"target\":\"test method\"",
     ];
 
     let collect_logs = follow_logs();
@@ -73,7 +73,7 @@ fn log_result_and_some_params_on_err() {
     }
 
     let expected_logs = [
-        "{\"logging.googleapis.com/sourceLocation\":{\"FILE\":\"infinite-tracing-macro/tests/infinite-tracing-macro-usage-api.rs\",\"LINE\":\"70\"},\"message\":\"do_something(a: 13, _password: <skipped>, _secret: <skipped>) => Err(13)\",\"severity\":\"ERROR\",\"span\":{\"a\":\"13\",\"module\":\"infinite_tracing_macro_usage_api\",\"ret\":\"Err(13)\"},\"target\":\"test method\"",
+        "{\"@type\":\"type.googleapis.com/google.devtools.clouderrorreporting.v1beta1.ReportedErrorEvent\",\"context\":{\"reportLocation\":{\"filePath\":\"infinite-tracing-macro/tests/infinite-tracing-macro-usage-api.rs\",\"lineNumber\":70}},\"logging.googleapis.com/sourceLocation\":{\"FILE\":\"infinite-tracing-macro/tests/infinite-tracing-macro-usage-api.rs\",\"LINE\":\"70\"},\"message\":\"do_something(a: 13, _password: <skipped>, _secret: <skipped>) => Err(13)\",\"severity\":\"ERROR\",\"span\":{\"a\":\"13\",\"module\":\"infinite_tracing_macro_usage_api\",\"ret\":\"Err(13)\"},\"target\":\"test method\"",
     ];
 
     let collect_logs = follow_logs();
@@ -85,6 +85,149 @@ fn log_result_and_some_params_on_err() {
     assert_eq!(observed_logs, expected_logs, "Wrong log contents");
 }
 
+#[test]
+fn log_error_chain_on_err() {
+    #[instrument(err, skip_all)]
+    fn do_something(a: u32) -> Result<u32, Error> {
+        let res: Result<u32, Error> = Err(TestErrorKind::Base.into());
+        res.err_context(TestErrorKind::Context(a))
+    }
+
+    let collect_logs = follow_logs();
+    let err = do_something(14).unwrap_err();
+    let observed_logs = collect_logs();
+    assert_eq!(observed_logs.len(), 1, "Wrong number of log lines");
+
+    let log: serde_json::Value = serde_json::from_str(&observed_logs[0]).unwrap();
+    let (outer, inner) = (err.location(), err.cause().unwrap().location());
+    assert_eq!(
+        log["logging.googleapis.com/sourceLocation"],
+        serde_json::json!({"FILE": outer.file(), "LINE": outer.line().to_string()}),
+        "The outermost frame location should be the source location"
+    );
+    assert_eq!(
+        log["span"]["error_chain"],
+        serde_json::json!([
+            {"kind": "Context", "message": "context 14", "file": outer.file(), "line": outer.line()},
+            {"kind": "Base", "message": "base", "file": inner.file(), "line": inner.line()},
+        ]),
+        "Wrong error chain"
+    );
+    assert_eq!(
+        log["span"]["error_fingerprint"],
+        err.fingerprint().to_string()
+    );
+    assert_eq!(
+        log["span"].get("ret"),
+        None,
+        "`ret` should be replaced by the chain"
+    );
+    assert_eq!(
+        log["@type"],
+        "type.googleapis.com/google.devtools.clouderrorreporting.v1beta1.ReportedErrorEvent"
+    );
+    assert_eq!(
+        log["stack_trace"],
+        format!(
+            "Context: context 14\n    at Context ({}:{})\n    at Base ({}:{})",
+            outer.file(),
+            outer.line(),
+            inner.file(),
+            inner.line()
+        ),
+        "The stack trace should be synthesized from the chain"
+    );
+}
+
+#[test]
+fn question_mark_conversions_record_their_location() {
+    #[instrument(err, skip_all)]
+    fn do_something() -> Result<u32, Error> {
+        Err(TestErrorKind::Base)?
+    }
+    let question_mark_line = line!() - 2;
+
+    let collect_logs = follow_logs();
+    let err = do_something().unwrap_err();
+    let observed_logs = collect_logs();
+    assert_eq!(observed_logs.len(), 1, "Wrong number of log lines");
+
+    assert_eq!(err.location().file(), file!());
+    assert_eq!(
+        err.location().line(),
+        question_mark_line,
+        "The leaf should be located at the `?`"
+    );
+    let log: serde_json::Value = serde_json::from_str(&observed_logs[0]).unwrap();
+    assert_eq!(
+        log["logging.googleapis.com/sourceLocation"],
+        serde_json::json!({"FILE": file!(), "LINE": question_mark_line.to_string()}),
+    );
+    assert_eq!(log["span"]["error_chain"][0]["line"], question_mark_line);
+}
+
+#[test]
+fn log_error_chain_of_bail() {
+    #[instrument(err, skip_all)]
+    fn do_something(a: u32) -> Result<u32, Error> {
+        if a == 0 {
+            infinite_errors::bail!(TestErrorKind::Base);
+        }
+        Ok(a)
+    }
+    let bail_line = line!() - 4;
+
+    let collect_logs = follow_logs();
+    let err = do_something(0).unwrap_err();
+    let observed_logs = collect_logs();
+    assert_eq!(observed_logs.len(), 1, "Wrong number of log lines");
+
+    assert_eq!(err.location().line(), bail_line);
+    let log: serde_json::Value = serde_json::from_str(&observed_logs[0]).unwrap();
+    assert_eq!(
+        log["span"]["error_chain"],
+        serde_json::json!([
+            {"kind": "Base", "message": "base", "file": file!(), "line": bail_line},
+        ]),
+        "Wrong error chain"
+    );
+    assert_eq!(
+        log["span"].get("ret"),
+        None,
+        "`ret` should be replaced by the chain"
+    );
+}
+
+#[test]
+fn instrumented_methods_return_borrows() {
+    struct Buffer {
+        bytes: Vec<u8>,
+    }
+
+    impl Buffer {
+        #[instrument(err)]
+        fn bytes(&mut self) -> Result<&mut Vec<u8>, Error> {
+            if self.bytes.is_empty() {
+                return Err(TestErrorKind::Base.into());
+            }
+            Ok(&mut self.bytes)
+        }
+
+        #[instrument(ok)]
+        fn first(&mut self) -> Result<&mut u8, Error> {
+            let bytes = self.bytes()?;
+            Ok(&mut bytes[0])
+        }
+    }
+
+    let collect_logs = follow_logs();
+    let mut buffer = Buffer { bytes: vec![1] };
+    buffer.bytes().unwrap().push(2);
+    *buffer.first().unwrap() = 0;
+    _ = collect_logs();
+    assert_eq!(buffer.bytes, [0, 2]);
+}
+
 /// Takes out the varying `time` and `traceId` fields of the log line
 fn normalize_log(log_line: &str) -> String {
     log_line
@@ -93,3 +236,20 @@ fn normalize_log(log_line: &str) -> String {
         .expect("Invalid log line")
         .to_string()
 }
+
+#[derive(Debug)]
+pub enum TestErrorKind {
+    Context(u32),
+    Base,
+}
+
+impl std::fmt::Display for TestErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TestErrorKind::Context(a) => write!(f, "context {a}"),
+            TestErrorKind::Base => write!(f, "base"),
+        }
+    }
+}
+
+infinite_errors::declare_error_type!(TestErrorKind);
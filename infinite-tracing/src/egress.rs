@@ -0,0 +1,109 @@
+//! Logging of the values returned by functions annotated with `#[instrument(err)]`,
+//! `#[instrument(ok)]` or `#[instrument(ret)]`.
+//!
+//! The record mimics the one `logcall` emits -- `do_something(a: 11) => Err(11)`, with the
+//! logged parameters and the returned value as `ret` --, except that an `Err` holding an
+//! `infinite-errors` error is logged as its structured chain: the `error_chain` and
+//! `error_fingerprint` values replace `ret`, and the outermost frame's location becomes the
+//! location of the record.
+
+use crate::error_chain::ErrorChain;
+use log::{Level, Metadata, Record};
+use std::fmt::Debug;
+
+/// A call of an instrumented function whose returned value is to be logged
+pub struct FnCall {
+    level: Level,
+    name: &'static str,
+    module_path: &'static str,
+    file: &'static str,
+    line: u32,
+    /// The parameters of the call, in order, and their `Debug` strings -- `None` for skipped ones
+    params: Vec<(&'static str, Option<String>)>,
+}
+
+impl FnCall {
+    /// Starts logging a call of the function `name`, whose `#[instrument]` annotation is at
+    /// `file:line`.\
+    /// Returns `None` if records with `level` are disabled for `module_path`, so the parameters
+    /// don't get formatted in vain.
+    pub fn start(
+        level: Level,
+        name: &'static str,
+        module_path: &'static str,
+        file: &'static str,
+        line: u32,
+    ) -> Option<Self> {
+        let metadata = Metadata::builder().level(level).target(module_path).build();
+        (level <= log::max_level() && log::logger().enabled(&metadata)).then(|| Self {
+            level,
+            name,
+            module_path,
+            file,
+            line,
+            params: Vec::new(),
+        })
+    }
+
+    /// Adds a parameter that is logged
+    pub fn param(mut self, name: &'static str, value: &impl Debug) -> Self {
+        self.params.push((name, Some(format!("{:?}", value))));
+        self
+    }
+
+    /// Adds a parameter that is not logged, shown as `<skipped>` in the message
+    pub fn skipped_param(mut self, name: &'static str) -> Self {
+        self.params.push((name, None));
+        self
+    }
+
+    /// Logs the value returned by the call, as `ret` -- or, for an `infinite-errors` error,
+    /// its `error_chain`, given by the instrumented function.
+    pub fn log_return(self, ret: &impl Debug, error_chain: Option<ErrorChain>) {
+        let params = self
+            .params
+            .iter()
+            .map(|(name, value)| format!("{}: {}", name, value.as_deref().unwrap_or("<skipped>")))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let ret = format!("{:?}", ret);
+        let message = format!("{}({}) => {}", self.name, params, ret);
+
+        let error_chain_json = error_chain.as_ref().map(ErrorChain::frames_json);
+        // GCP Error Reporting groups ERROR events by their stack trace
+        let stack_trace = error_chain
+            .as_ref()
+            .filter(|_| self.level == Level::Error)
+            .map(ErrorChain::stack_trace);
+
+        let mut key_values: Vec<(&str, &str)> = self
+            .params
+            .iter()
+            .filter_map(|(name, value)| Some((*name, value.as_deref()?)))
+            .collect();
+        let (mut file, mut line) = (self.file, self.line);
+        match (&error_chain, &error_chain_json) {
+            (Some(error_chain), Some(error_chain_json)) => {
+                if let Some(outermost_frame) = error_chain.frames.first() {
+                    (file, line) = (outermost_frame.file, outermost_frame.line);
+                }
+                key_values.push(("error_chain", error_chain_json));
+                key_values.push(("error_fingerprint", &error_chain.fingerprint));
+                key_values.extend(stack_trace.as_deref().map(|s| ("stack_trace", s)));
+            }
+            _ => key_values.push(("ret", &ret)),
+        }
+
+        log::logger().log(
+            &Record::builder()
+                .level(self.level)
+                .target(self.module_path)
+                .module_path_static(Some(self.module_path))
+                .file_static(Some(file))
+                .line(Some(line))
+                .args(format_args!("{}", message))
+                .key_values(&key_values.as_slice())
+                .build(),
+        );
+    }
+}
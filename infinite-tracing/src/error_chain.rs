@@ -0,0 +1,183 @@
+//! Structured logging of `infinite-errors` chains returned by instrumented functions.
+//!
+//! When a function annotated with `#[instrument(err)]` or `#[instrument(ret)]` returns
+//! an `Err` holding an `infinite-errors` error, its chain is built from the returned value and
+//! logged with it, as the `error_chain` JSON array -- one entry per frame --, using the outermost
+//! frame's location as the log line's `sourceLocation`. For ERROR events, it is also rendered as
+//! the `stack_trace` picked up by GCP Error Reporting.
+
+use infinite_errors::{ErrorType, VariantName};
+use serde::Serialize;
+use std::fmt::{Debug, Display};
+
+/// The chain of an `infinite-errors` error returned by an instrumented function
+pub struct ErrorChain {
+    pub frames: Vec<ErrorChainFrame>,
+    pub fingerprint: String,
+    /// The runtime backtrace captured by `infinite-errors`, if its `backtrace` feature is enabled
+    pub backtrace: Option<String>,
+}
+
+/// A single frame of an [ErrorChain], as emitted in the `error_chain` array
+#[derive(Debug, Serialize)]
+pub struct ErrorChainFrame {
+    pub kind: String,
+    pub message: String,
+    pub file: &'static str,
+    pub line: u32,
+}
+
+impl ErrorChain {
+    fn new<E>(err: &E) -> Self
+    where
+        E: ErrorType,
+        E::ErrorKind: Debug + Display,
+    {
+        let frames = err
+            .chain()
+            .map(|frame| ErrorChainFrame {
+                kind: VariantName(frame.kind()).to_string(),
+                message: frame.kind().to_string(),
+                file: frame.location().file(),
+                line: frame.location().line(),
+            })
+            .collect();
+        Self {
+            frames,
+            fingerprint: err.fingerprint().to_string(),
+            backtrace: err.backtrace().map(ToString::to_string),
+        }
+    }
+
+    /// Renders [Self::frames] as a JSON array
+    pub fn frames_json(&self) -> String {
+        serde_json::to_string(&self.frames)
+            .expect("`infinite-tracing`: error chain frames can always be serialized")
+    }
+
+    /// Renders this chain as a stack trace GCP Error Reporting is able to parse -- in the
+    /// JavaScript format, as Rust's isn't supported:
+    /// ```nocompile
+    /// LoadUser: failed to load user 7
+    ///     at LoadUser (src/user.rs:42)
+    ///     at Database (src/db.rs:10)
+    /// ```
+    /// If a runtime backtrace was captured, its frames are used instead of the chain's locations.
+    pub fn stack_trace(&self) -> String {
+        let mut stack_trace = match self.frames.first() {
+            Some(outermost_frame) => {
+                format!("{}: {}", outermost_frame.kind, outermost_frame.message)
+            }
+            None => String::from("Error"),
+        };
+        match &self.backtrace {
+            Some(backtrace) => {
+                for (function, location) in backtrace_frames(backtrace) {
+                    stack_trace.push_str(&format!("\n    at {function} ({location})"));
+                }
+            }
+            None => {
+                for frame in &self.frames {
+                    stack_trace.push_str(&format!(
+                        "\n    at {} ({}:{})",
+                        frame.kind, frame.file, frame.line
+                    ));
+                }
+            }
+        }
+        stack_trace
+    }
+}
+
+/// Extracts the `(function, location)` pairs of a `std::backtrace::Backtrace` rendering, where frames
+/// look like:
+/// ```nocompile
+///    3: app::load_user
+///              at ./src/user.rs:42:5
+/// ```
+/// Frames without a location are skipped.
+fn backtrace_frames(backtrace: &str) -> Vec<(&str, &str)> {
+    let mut frames = Vec::new();
+    let mut function = None;
+    for line in backtrace.lines().map(str::trim) {
+        if let Some(location) = line.strip_prefix("at ") {
+            if let Some(function) = function.take() {
+                frames.push((function, location));
+            }
+        } else if let Some((index, name)) = line.split_once(": ") {
+            function = index.parse::<usize>().is_ok().then_some(name);
+        }
+    }
+    frames
+}
+
+/// Implemented by `Result`s whose error is an `infinite-errors` error.\
+/// Together with [NotErrorChainResult], this relies on auto-ref specialization: calling
+/// `(&ret).__infinite_tracing_error_chain()` picks this trait whenever it applies.
+#[doc(hidden)]
+pub trait ErrorChainResult {
+    fn __infinite_tracing_error_chain(&self) -> Option<ErrorChain>;
+}
+
+impl<T, E> ErrorChainResult for Result<T, E>
+where
+    E: ErrorType,
+    E::ErrorKind: Debug + Display,
+{
+    fn __infinite_tracing_error_chain(&self) -> Option<ErrorChain> {
+        self.as_ref().err().map(ErrorChain::new)
+    }
+}
+
+/// Fallback for any other returned value, which has no chain
+#[doc(hidden)]
+pub trait NotErrorChainResult {
+    fn __infinite_tracing_error_chain(&self) -> Option<ErrorChain> {
+        None
+    }
+}
+
+impl<T> NotErrorChainResult for &T {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_chain(backtrace: Option<&str>) -> ErrorChain {
+        ErrorChain {
+            frames: vec![
+                ErrorChainFrame {
+                    kind: String::from("LoadUser"),
+                    message: String::from("failed to load user 7"),
+                    file: "src/user.rs",
+                    line: 42,
+                },
+                ErrorChainFrame {
+                    kind: String::from("Database"),
+                    message: String::from("connection refused"),
+                    file: "src/db.rs",
+                    line: 10,
+                },
+            ],
+            fingerprint: String::from("562e74d09512b32f"),
+            backtrace: backtrace.map(String::from),
+        }
+    }
+
+    #[test]
+    fn stack_trace_from_chain_locations() {
+        assert_eq!(
+            error_chain(None).stack_trace(),
+            "LoadUser: failed to load user 7\n    at LoadUser (src/user.rs:42)\n    at Database (src/db.rs:10)"
+        );
+    }
+
+    #[test]
+    fn stack_trace_from_backtrace() {
+        let backtrace = "   0: app::db::connect\n             at ./src/db.rs:8:9\n   1: std::rt::lang_start_internal\n   2: app::main\n             at ./src/main.rs:3:5\n";
+        assert_eq!(
+            error_chain(Some(backtrace)).stack_trace(),
+            "LoadUser: failed to load user 7\n    at app::db::connect (./src/db.rs:8:9)\n    at app::main (./src/main.rs:3:5)"
+        );
+    }
+}
@@ -1,3 +1,5 @@
+mod egress;
+mod error_chain;
 mod features;
 mod minitrace_glue;
 mod structured_logger_glue;
@@ -7,6 +9,7 @@ use std::borrow::Cow;
 
 pub use infinite_tracing_macro::instrument;
 pub use minitrace::full_name;
+pub use minitrace_glue::ServiceContext;
 
 /// Should be executed at the application start -- once.
 /// Example:
@@ -21,6 +24,53 @@ pub fn setup_infinite_tracing(output_fn: impl std::io::Write + Send + 'static) {
     minitrace_glue::setup_minitrace(output_fn);
 }
 
+/// Same as [setup_infinite_tracing], but also identifies the service in the ERROR log events,
+/// as the `serviceContext` used by GCP Error Reporting.
+/// Example:
+/// ```nocompile
+///   use infinite_tracing::*;
+///   setup_infinite_tracing_with_service_context(
+///       std::io::stdout,
+///       ServiceContext {
+///           service: String::from(env!("CARGO_PKG_NAME")),
+///           version: String::from(env!("CARGO_PKG_VERSION")),
+///       },
+///   );
+pub fn setup_infinite_tracing_with_service_context(
+    output_fn: impl std::io::Write + Send + 'static,
+    service_context: ServiceContext,
+) {
+    structured_logger_glue::setup_structured_logger();
+    minitrace_glue::setup_minitrace_with_service_context(output_fn, service_context);
+}
+
+/// Items used by the code generated by `#[instrument]`. Not part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    use std::future::Future;
+
+    #[doc(hidden)]
+    pub use crate::egress::FnCall;
+    #[doc(hidden)]
+    pub use crate::error_chain::{ErrorChain, ErrorChainResult, NotErrorChainResult};
+    #[doc(hidden)]
+    pub use log::Level;
+
+    /// Calls the closure a sync instrumented body is wrapped in, binding its value once. As it
+    /// is only called once, the closure may return borrows of what it captures, like `self`.
+    #[doc(hidden)]
+    pub fn call_once<T, F: FnOnce() -> T>(body: F) -> T {
+        body()
+    }
+
+    /// Ties the output of an `async` block to the return type of the instrumented function,
+    /// so `?` may be used inside of it
+    #[doc(hidden)]
+    pub fn typed_future<T, F: Future<Output = T>>(future: F) -> F {
+        future
+    }
+}
+
 /// Should be executed at the application shutdown -- or else some log events may be lost,
 /// specially if the `Writer` given to [setup_infinite_tracing] is buffered.
 pub fn teardown_intinite_tracing() {
@@ -1,22 +1,54 @@
 use minitrace::collector::{Config, Reporter, SpanRecord};
 use serde_json::json;
 
+/// `@type` of the log entries GCP Error Reporting should pick up -- even without a stack trace
+const REPORTED_ERROR_EVENT_TYPE: &str =
+    "type.googleapis.com/google.devtools.clouderrorreporting.v1beta1.ReportedErrorEvent";
+
+/// Identifies the service emitting the logs, for GCP Error Reporting.\
+/// Emitted as the `serviceContext` of ERROR log events.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceContext {
+    /// The service name -- like `payments-api`
+    pub service: String,
+    /// The service version -- like `1.4.2` or a commit hash
+    pub version: String,
+}
+
 pub fn setup_minitrace(output_fn: impl std::io::Write + Send + 'static) {
     let json_reporter = JsonReporter::new(output_fn);
     minitrace::set_reporter(json_reporter, Config::default());
 }
 
+pub fn setup_minitrace_with_service_context(
+    output_fn: impl std::io::Write + Send + 'static,
+    service_context: ServiceContext,
+) {
+    let json_reporter = JsonReporter::new(output_fn).with_service_context(service_context);
+    minitrace::set_reporter(json_reporter, Config::default());
+}
+
 pub fn teardown_minitrace() {
     minitrace::flush();
 }
 
 pub struct JsonReporter<WriteImpl: std::io::Write> {
     writer: WriteImpl,
+    service_context: Option<ServiceContext>,
 }
 
 impl<WriteImpl: std::io::Write> JsonReporter<WriteImpl> {
     pub fn new(writer: WriteImpl) -> Self {
-        Self { writer }
+        Self {
+            writer,
+            service_context: None,
+        }
+    }
+
+    /// Identifies the service in the ERROR log events, as their `serviceContext`
+    pub fn with_service_context(mut self, service_context: ServiceContext) -> Self {
+        self.service_context = Some(service_context);
+        self
     }
 }
 
@@ -31,6 +63,7 @@ impl<WriteImpl: std::io::Write + Send + 'static> Reporter for JsonReporter<Write
                 let mut timestamp = "<MISSING TIMESTAMP>";
                 let mut file = "";
                 let mut line = "";
+                let mut stack_trace = None;
                 let mut structured_fields = serde_json::Map::new();
                 for (property_key, property_value) in &event.properties {
                     match property_key.as_ref() {
@@ -38,13 +71,20 @@ impl<WriteImpl: std::io::Write + Send + 'static> Reporter for JsonReporter<Write
                         "message" => message = property_value,
                         "file" => file = property_value,
                         "line" => line = property_value,
+                        "stack_trace" => stack_trace = Some(property_value.as_ref()),
+                        "error_chain" => {
+                            // already a JSON array: emit it structured, rather than as a string
+                            let error_chain = serde_json::from_str(property_value)
+                                .unwrap_or_else(|_| json!(property_value));
+                            structured_fields.insert(property_key.to_string(), error_chain);
+                        }
                         _ => {
                             structured_fields
                                 .insert(property_key.to_string(), json!(property_value));
                         }
                     }
                 }
-                let log_line = json!({
+                let mut log_line = json!({
                     "time": timestamp,
                     "target": target,
                     "logging.googleapis.com/sourceLocation": {"FILE": file, "LINE": line},
@@ -53,6 +93,15 @@ impl<WriteImpl: std::io::Write + Send + 'static> Reporter for JsonReporter<Write
                     "severity": severity,
                     "message": message,
                 });
+                if severity == "ERROR" {
+                    add_error_reporting_fields(
+                        &mut log_line,
+                        file,
+                        line,
+                        stack_trace,
+                        self.service_context.as_ref(),
+                    );
+                }
                 let mut write_op = || {
                     serde_json::to_writer(&mut self.writer, &log_line)?;
                     self.writer.write(b"\n")
@@ -62,3 +111,75 @@ impl<WriteImpl: std::io::Write + Send + 'static> Reporter for JsonReporter<Write
         }
     }
 }
+
+/// Adds the fields GCP Error Reporting needs to group a log entry as an error:
+/// the `ReportedErrorEvent` `@type`, the `stack_trace` -- if any --, the `context.reportLocation`
+/// and the `serviceContext`, if one was configured.
+fn add_error_reporting_fields(
+    log_line: &mut serde_json::Value,
+    file: &str,
+    line: &str,
+    stack_trace: Option<&str>,
+    service_context: Option<&ServiceContext>,
+) {
+    let Some(log_line) = log_line.as_object_mut() else {
+        return;
+    };
+    log_line.insert("@type".to_string(), json!(REPORTED_ERROR_EVENT_TYPE));
+    if let Some(stack_trace) = stack_trace {
+        log_line.insert("stack_trace".to_string(), json!(stack_trace));
+    }
+    if let Ok(line) = line.parse::<u32>() {
+        log_line.insert(
+            "context".to_string(),
+            json!({"reportLocation": {"filePath": file, "lineNumber": line}}),
+        );
+    }
+    if let Some(service_context) = service_context {
+        log_line.insert(
+            "serviceContext".to_string(),
+            json!({"service": service_context.service, "version": service_context.version}),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_reporting_fields() {
+        let mut log_line = json!({"severity": "ERROR"});
+        let service_context = ServiceContext {
+            service: String::from("api"),
+            version: String::from("1.0.0"),
+        };
+        add_error_reporting_fields(
+            &mut log_line,
+            "src/main.rs",
+            "42",
+            Some("Load: failed\n    at Load (src/main.rs:42)"),
+            Some(&service_context),
+        );
+        assert_eq!(
+            log_line,
+            json!({
+                "severity": "ERROR",
+                "@type": REPORTED_ERROR_EVENT_TYPE,
+                "stack_trace": "Load: failed\n    at Load (src/main.rs:42)",
+                "context": {"reportLocation": {"filePath": "src/main.rs", "lineNumber": 42}},
+                "serviceContext": {"service": "api", "version": "1.0.0"},
+            })
+        );
+    }
+
+    #[test]
+    fn error_reporting_fields_without_location_nor_stack_trace() {
+        let mut log_line = json!({"severity": "ERROR"});
+        add_error_reporting_fields(&mut log_line, "", "", None, None);
+        assert_eq!(
+            log_line,
+            json!({"severity": "ERROR", "@type": REPORTED_ERROR_EVENT_TYPE})
+        );
+    }
+}